@@ -1,12 +1,25 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	opaque::SessionKeys, AccountId, BalancesConfig, CouncilConfig, GenesisConfig, GrandpaConfig,
+	OcwModule, OcwModuleConfig, SessionConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
+#[cfg(not(feature = "babe"))]
+use node_template_runtime::AuraConfig;
+#[cfg(feature = "babe")]
+use node_template_runtime::{BabeConfig, BABE_GENESIS_EPOCH_CONFIG};
+#[cfg(not(feature = "npos"))]
+use node_template_runtime::ValidatorSetConfig;
+#[cfg(feature = "npos")]
+use node_template_runtime::{Balance, ImOnlineConfig, ImOnlineId, StakerStatus, StakingConfig};
 use sc_service::ChainType;
+#[cfg(not(feature = "babe"))]
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+#[cfg(feature = "babe")]
+use sp_consensus_babe::AuthorityId as BabeId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
+#[cfg(feature = "npos")]
+use sp_runtime::Perbill;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -31,9 +44,70 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// The block authoring key of a validator.
+#[cfg(not(feature = "babe"))]
+pub type AuthoringId = AuraId;
+/// The block authoring key of a validator.
+#[cfg(feature = "babe")]
+pub type AuthoringId = BabeId;
+
+/// A genesis validator: its account and its block authoring and Grandpa session keys.
+#[cfg(not(feature = "npos"))]
+pub type AuthorityKeys = (AccountId, AuthoringId, GrandpaId);
+
+/// A genesis validator: its stash and controller accounts and its block authoring, Grandpa and
+/// ImOnline session keys.
+#[cfg(feature = "npos")]
+pub type AuthorityKeys = (AccountId, AccountId, AuthoringId, GrandpaId, ImOnlineId);
+
+/// The amount each genesis validator bonds from its stash.
+#[cfg(feature = "npos")]
+const STASH: Balance = 1 << 50;
+
+/// Generate a validator account with its block authoring and Grandpa session keys.
+#[cfg(not(feature = "npos"))]
+pub fn authority_keys_from_seed(s: &str) -> AuthorityKeys {
+	(
+		get_account_id_from_seed::<sr25519::Public>(s),
+		get_from_seed::<AuthoringId>(s),
+		get_from_seed::<GrandpaId>(s),
+	)
+}
+
+/// Generate a validator's stash (`<seed>//stash`) and controller accounts with its block
+/// authoring, Grandpa and ImOnline session keys.
+#[cfg(feature = "npos")]
+pub fn authority_keys_from_seed(s: &str) -> AuthorityKeys {
+	(
+		get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", s)),
+		get_account_id_from_seed::<sr25519::Public>(s),
+		get_from_seed::<AuthoringId>(s),
+		get_from_seed::<GrandpaId>(s),
+		get_from_seed::<ImOnlineId>(s),
+	)
+}
+
+#[cfg(not(feature = "npos"))]
+fn session_keys(authority: &AuthorityKeys) -> SessionKeys {
+	SessionKeys {
+		#[cfg(not(feature = "babe"))]
+		aura: authority.1.clone(),
+		#[cfg(feature = "babe")]
+		babe: authority.1.clone(),
+		grandpa: authority.2.clone(),
+	}
+}
+
+#[cfg(feature = "npos")]
+fn session_keys(authority: &AuthorityKeys) -> SessionKeys {
+	SessionKeys {
+		#[cfg(not(feature = "babe"))]
+		aura: authority.2.clone(),
+		#[cfg(feature = "babe")]
+		babe: authority.2.clone(),
+		grandpa: authority.3.clone(),
+		im_online: authority.4.clone(),
+	}
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -59,6 +133,11 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Council members
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
 				true,
 			)
 		},
@@ -106,6 +185,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Council members
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				true,
 			)
 		},
@@ -125,9 +210,10 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	initial_authorities: Vec<AuthorityKeys>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	council_members: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -137,19 +223,64 @@ fn testnet_genesis(
 			changes_trie_config: Default::default(),
 		},
 		balances: BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// Configure endowed accounts, and the pot paying oracle rewards, with initial balance of 1 << 60.
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.chain(std::iter::once(OcwModule::reward_pot()))
+				.map(|k| (k, 1 << 60))
+				.collect(),
+		},
+		#[cfg(not(feature = "npos"))]
+		validator_set: ValidatorSetConfig {
+			initial_validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+		},
+		// Bond the genesis validators and elect them for the first era.
+		#[cfg(feature = "npos")]
+		staking: StakingConfig {
+			validator_count: initial_authorities.len() as u32,
+			minimum_validator_count: initial_authorities.len() as u32,
+			invulnerables: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			slash_reward_fraction: Perbill::from_percent(10),
+			stakers: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.1.clone(), STASH, StakerStatus::Validator))
+				.collect(),
+			..Default::default()
 		},
-		aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+		// Aura (or BABE) and Grandpa authorities are set from the session keys.
+		session: SessionConfig {
+			keys: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.0.clone(), session_keys(x)))
+				.collect(),
 		},
-		grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+		#[cfg(not(feature = "babe"))]
+		aura: AuraConfig { authorities: vec![] },
+		#[cfg(feature = "babe")]
+		babe: BabeConfig { authorities: vec![], epoch_config: Some(BABE_GENESIS_EPOCH_CONFIG) },
+		grandpa: GrandpaConfig { authorities: vec![] },
+		#[cfg(feature = "npos")]
+		im_online: ImOnlineConfig { keys: vec![] },
+		ocw_module: OcwModuleConfig {
+			// The sudo account doubles as the first price oracle.
+			oracles: vec![root_key.clone()],
+			// DOT/USD with four decimals, aggregated every ten blocks.
+			feeds: vec![(
+				0,
+				b"https://min-api.cryptocompare.com/data/price?fsym=DOT&tsyms=USD".to_vec(),
+				b"USD".to_vec(),
+				4,
+				10,
+			)],
 		},
 		sudo: SudoConfig {
-			// Assign network admin rights.
+			// Assign network admin rights until governance retires the key.
 			key: root_key,
 		},
+		council: CouncilConfig { phantom: Default::default(), members: council_members },
+		democracy: Default::default(),
+		treasury: Default::default(),
 		transaction_payment: Default::default(),
 	}
 }
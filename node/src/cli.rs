@@ -35,6 +35,12 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Build a PoE batch Merkle tree and inclusion proofs from a directory.
+	PoeBatch(crate::poe_batch::PoeBatchCmd),
+
+	/// Export a PoE claim proof bundle for offline verification.
+	ExportProofBundle(crate::poe_proof::ExportProofBundleCmd),
+
 	/// The custom benchmark subcommand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
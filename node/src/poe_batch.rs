@@ -0,0 +1,87 @@
+//! Node-side helper for PoE batch notarization.
+//!
+//! Hashes every file of a directory, builds the Merkle tree used by
+//! `PoeModule::create_batch_claim` and writes the root together with an
+//! inclusion proof for each file, so any document can later be checked with
+//! the `poe_verifyInclusion` RPC.
+
+use node_template_runtime::pallet_poe::merkle;
+use sc_cli::{Error, Result};
+use serde::Serialize;
+use sp_core::{hashing::blake2_256, H256};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `poe-batch` command.
+#[derive(Debug, StructOpt)]
+pub struct PoeBatchCmd {
+	/// Directory whose files are notarized as one batch.
+	#[structopt(long, parse(from_os_str))]
+	pub dir: PathBuf,
+
+	/// Output JSON file; printed to stdout when omitted.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+}
+
+/// Merkle root and the inclusion proofs of every file in the batch.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Batch {
+	root: H256,
+	leaf_count: u32,
+	leaves: Vec<Leaf>,
+}
+
+/// Inclusion proof of a single file.
+#[derive(Serialize)]
+struct Leaf {
+	file: String,
+	leaf: H256,
+	index: u32,
+	proof: Vec<H256>,
+}
+
+impl PoeBatchCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<()> {
+		let mut files = fs::read_dir(&self.dir)?
+			.map(|entry| entry.map(|e| e.path()))
+			.collect::<std::io::Result<Vec<_>>>()?;
+		files.retain(|path| path.is_file());
+		// Sort so that the same directory always yields the same root.
+		files.sort();
+		if files.is_empty() {
+			return Err(Error::Input(format!("No files found in {}", self.dir.display())))
+		}
+		let leaf_count = u32::try_from(files.len())
+			.map_err(|_| Error::Input("Too many files for a single batch".into()))?;
+
+		let hashes = files
+			.iter()
+			.map(|path| fs::read(path).map(|data| H256(blake2_256(&data))))
+			.collect::<std::io::Result<Vec<_>>>()?;
+		let layers = merkle::build_layers(&hashes);
+		let root = layers.last().and_then(|l| l.first()).copied().expect("files is not empty; qed");
+
+		let leaves = files
+			.iter()
+			.zip(hashes.iter())
+			.enumerate()
+			.map(|(index, (path, leaf))| Leaf {
+				file: path.display().to_string(),
+				leaf: *leaf,
+				index: index as u32,
+				proof: merkle::merkle_proof(&layers, index),
+			})
+			.collect();
+
+		let json = serde_json::to_string_pretty(&Batch { root, leaf_count, leaves })
+			.map_err(|e| Error::Input(e.to_string()))?;
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,94 @@
+//! Export of PoE claim proof bundles for offline verification.
+//!
+//! The bundle holds the finalized header and a storage proof of the claim,
+//! and is checked with `pallet_poe_verifier::verify_bundle` without a node.
+
+use codec::Encode;
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Moment};
+use pallet_poe_rpc::PoeRuntimeApi;
+use pallet_poe_verifier::ProofBundle;
+use sc_cli::{CliConfiguration, DatabaseParams, Error, PruningParams, Result, SharedParams};
+use sc_client_api::ProofProvider;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::generic::BlockId;
+use std::{fs, path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+
+/// The `export-proof-bundle` command.
+#[derive(Debug, StructOpt)]
+pub struct ExportProofBundleCmd {
+	/// Hex encoded claim, e.g. `0x0001`.
+	#[structopt(long)]
+	pub claim: Bytes,
+
+	/// File the SCALE encoded bundle is written to.
+	#[structopt(long, parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// Block hash to prove against; defaults to the last finalized block.
+	#[structopt(long)]
+	pub block: Option<H256>,
+
+	/// Name of the PoE pallet in `construct_runtime!`.
+	#[structopt(long, default_value = "PoeModule")]
+	pub pallet_name: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportProofBundleCmd {
+	/// Run the command.
+	pub fn run<C>(&self, client: Arc<C>) -> Result<()>
+	where
+		C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block>,
+		C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber, Moment>,
+	{
+		let hash = self.block.unwrap_or_else(|| client.info().finalized_hash);
+		let at = BlockId::hash(hash);
+		let claim = self.claim.to_vec();
+
+		let keys = client
+			.runtime_api()
+			.claim_storage_keys(&at, claim.clone())
+			.map_err(|e| Error::Application(Box::new(e)))?;
+		let header = client
+			.header(at)?
+			.ok_or_else(|| Error::Input(format!("Unknown block {:?}", hash)))?;
+		let proof = client.read_proof(&at, &mut keys.iter().map(|k| k.as_slice()))?;
+
+		let bundle = ProofBundle {
+			header,
+			pallet_name: self.pallet_name.as_bytes().to_vec(),
+			claim,
+			proof: proof.iter_nodes().collect(),
+		};
+		fs::write(&self.output, bundle.encode())?;
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportProofBundleCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
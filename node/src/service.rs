@@ -1,14 +1,18 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::ExecutorProvider;
+use sc_client_api::{Backend, ExecutorProvider};
+#[cfg(not(feature = "babe"))]
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+#[cfg(feature = "babe")]
+use sc_consensus_babe::SlotProportion;
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus::SlotData;
+#[cfg(not(feature = "babe"))]
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use std::{sync::Arc, time::Duration};
 
@@ -36,6 +40,23 @@ type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullGrandpaBlockImport =
+	sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
+
+/// The block import used for authoring: GRANDPA's, wrapped by BABE's when authoring with BABE.
+#[cfg(not(feature = "babe"))]
+type FullBlockImport = FullGrandpaBlockImport;
+/// The block import used for authoring: GRANDPA's, wrapped by BABE's when authoring with BABE.
+#[cfg(feature = "babe")]
+type FullBlockImport =
+	sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>;
+
+/// State shared between the import queue and the authoring task. Aura needs none.
+#[cfg(not(feature = "babe"))]
+type FullAuthoringLink = ();
+/// State shared between the import queue and the authoring task. Aura needs none.
+#[cfg(feature = "babe")]
+type FullAuthoringLink = sc_consensus_babe::BabeLink<Block>;
 
 pub fn new_partial(
 	config: &Configuration,
@@ -47,13 +68,9 @@ pub fn new_partial(
 		sc_consensus::DefaultImportQueue<Block, FullClient>,
 		sc_transaction_pool::FullPool<Block, FullClient>,
 		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
+			FullBlockImport,
 			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+			FullAuthoringLink,
 			Option<Telemetry>,
 		),
 	>,
@@ -110,32 +127,75 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+	#[cfg(not(feature = "babe"))]
+	let (block_import, authoring_link, import_queue) = {
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
 
-	let import_queue =
-		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
-			justification_import: Some(Box::new(grandpa_block_import.clone())),
-			client: client.clone(),
-			create_inherent_data_providers: move |_, ()| async move {
+		let import_queue =
+			sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
+				block_import: grandpa_block_import.clone(),
+				justification_import: Some(Box::new(grandpa_block_import.clone())),
+				client: client.clone(),
+				create_inherent_data_providers: move |_, ()| async move {
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
+
+					Ok((timestamp, slot))
+				},
+				spawner: &task_manager.spawn_essential_handle(),
+				can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
+					client.executor().clone(),
+				),
+				registry: config.prometheus_registry(),
+				check_for_equivocation: Default::default(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
+			})?;
+
+		(grandpa_block_import, (), import_queue)
+	};
+
+	#[cfg(feature = "babe")]
+	let (block_import, authoring_link, import_queue) = {
+		let justification_import = grandpa_block_import.clone();
+
+		let (block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client)?,
+			grandpa_block_import,
+			client.clone(),
+		)?;
+
+		let slot_duration = babe_link.config().slot_duration();
+
+		let import_queue = sc_consensus_babe::import_queue(
+			babe_link.clone(),
+			block_import.clone(),
+			Some(Box::new(justification_import)),
+			client.clone(),
+			select_chain.clone(),
+			move |_, ()| async move {
 				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+					sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
 						*timestamp,
 						slot_duration,
 					);
 
 				Ok((timestamp, slot))
 			},
-			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
-				client.executor().clone(),
-			),
-			registry: config.prometheus_registry(),
-			check_for_equivocation: Default::default(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+			telemetry.as_ref().map(|x| x.handle()),
+		)?;
+
+		(block_import, babe_link, import_queue)
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -145,7 +205,7 @@ pub fn new_partial(
 		keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (grandpa_block_import, grandpa_link, telemetry),
+		other: (block_import, grandpa_link, authoring_link, telemetry),
 	})
 }
 
@@ -158,6 +218,8 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 
 /// Builds a new service for a full client.
 pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+	// Aura keeps no state between import and authoring, so its link is unused.
+	#[cfg_attr(not(feature = "babe"), allow(unused_variables))]
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -166,7 +228,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		mut keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (block_import, grandpa_link, mut telemetry),
+		other: (block_import, grandpa_link, authoring_link, mut telemetry),
 	} = new_partial(&config)?;
 
 	if let Some(url) = &config.keystore_remote {
@@ -180,6 +242,17 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		};
 	}
 
+	// On development chains give the offchain worker a signing key so `pallet_ocw` can submit
+	// transactions out of the box. Other chains insert it through `author_insertKey`.
+	if config.offchain_worker.enabled && config.chain_spec.id() == "dev" {
+		sp_keystore::SyncCryptoStore::sr25519_generate_new(
+			&*keystore_container.sync_keystore(),
+			node_template_runtime::pallet_ocw::KEY_TYPE,
+			Some("//Alice"),
+		)
+		.map_err(|e| ServiceError::Other(format!("Failed to insert offchain worker key: {}", e)))?;
+	}
+
 	config.network.extra_sets.push(sc_finality_grandpa::grandpa_peers_set_config());
 	let warp_sync = Arc::new(sc_finality_grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
@@ -218,10 +291,15 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let offchain_storage = backend.offchain_storage();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				offchain_storage: offchain_storage.clone(),
+				deny_unsafe,
+			};
 
 			Ok(crate::rpc::create_full(deps))
 		})
@@ -254,42 +332,82 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let can_author_with =
 			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
-		let raw_slot_duration = slot_duration.slot_duration();
+		#[cfg(not(feature = "babe"))]
+		{
+			let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+			let raw_slot_duration = slot_duration.slot_duration();
+
+			let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
+				StartAuraParams {
+					slot_duration,
+					client: client.clone(),
+					select_chain,
+					block_import,
+					proposer_factory,
+					create_inherent_data_providers: move |_, ()| async move {
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+						let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+								*timestamp,
+								raw_slot_duration,
+							);
+
+						Ok((timestamp, slot))
+					},
+					force_authoring,
+					backoff_authoring_blocks,
+					keystore: keystore_container.sync_keystore(),
+					can_author_with,
+					sync_oracle: network.clone(),
+					justification_sync_link: network.clone(),
+					block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
+					max_block_proposal_slot_portion: None,
+					telemetry: telemetry.as_ref().map(|x| x.handle()),
+				},
+			)?;
+
+			// the AURA authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		}
 
-		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
-			StartAuraParams {
-				slot_duration,
+		#[cfg(feature = "babe")]
+		{
+			let slot_duration = authoring_link.config().slot_duration();
+
+			let babe = sc_consensus_babe::start_babe(sc_consensus_babe::BabeParams {
+				keystore: keystore_container.sync_keystore(),
 				client: client.clone(),
 				select_chain,
+				env: proposer_factory,
 				block_import,
-				proposer_factory,
+				sync_oracle: network.clone(),
+				justification_sync_link: network.clone(),
 				create_inherent_data_providers: move |_, ()| async move {
 					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+						sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
 							*timestamp,
-							raw_slot_duration,
+							slot_duration,
 						);
 
 					Ok((timestamp, slot))
 				},
 				force_authoring,
 				backoff_authoring_blocks,
-				keystore: keystore_container.sync_keystore(),
+				babe_link: authoring_link,
 				can_author_with,
-				sync_oracle: network.clone(),
-				justification_sync_link: network.clone(),
 				block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
 				max_block_proposal_slot_portion: None,
 				telemetry: telemetry.as_ref().map(|x| x.handle()),
-			},
-		)?;
+			})?;
 
-		// the AURA authoring task is considered essential, i.e. if it
-		// fails we take down the service with it.
-		task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+			// the BABE authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("babe-proposer", babe);
+		}
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
@@ -0,0 +1,235 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! 资产支付交易费用模块
+//!
+//! 治理(`ForceOrigin`)批准可用于支付费用的资产并为每种资产设置汇率。
+//! 账户选择已批准的费用资产后,交易费用(含小费)按汇率折算,不为零的费用至少收取1个单位,
+//! 从账户的资产余额中扣除;与原生币费用相同,小费全部及其余费用的 `AuthorShare` 比例归出块人,
+//! 其余归收款账户。没有选择费用资产、或所选资产已被移出批准列表的账户仍用原生币支付。
+//! `AssetConversionAdapter` 作为 `pallet_transaction_payment` 的 `OnChargeTransaction` 使用。
+//! 批准资产前收款账户需要持有该资产至少最小余额的数量,之后的转出都保证收款账户存活;
+//! 出块人没有该资产且分得的数量低于最小余额时,这部分费用留在收款账户。
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::traits::tokens::fungibles::{Inspect, Transfer};
+use pallet_transaction_payment::OnChargeTransaction;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, Saturating, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+use sp_std::marker::PhantomData;
+
+/// 原生币余额类型
+pub type BalanceOf<T> = <<T as Config>::NativeAdapter as OnChargeTransaction<T>>::Balance;
+/// 资产id类型
+pub type AssetIdOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+/// 资产余额类型
+pub type AssetBalanceOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{AssetBalanceOf, AssetIdOf, BalanceOf};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::tokens::fungibles::{Inspect, Transfer},
+	};
+	use frame_system::pallet_prelude::*;
+	use pallet_transaction_payment::OnChargeTransaction;
+	use sp_runtime::{
+		traits::{One, Zero},
+		FixedPointNumber, FixedU128, Permill, SaturatedConversion,
+	};
+
+	/// 配置接口
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
+		/// 事件
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// 可用于支付费用的资产
+		type Assets: Inspect<Self::AccountId> + Transfer<Self::AccountId>;
+		/// 用原生币支付费用的方式
+		type NativeAdapter: OnChargeTransaction<Self>;
+		/// 批准费用资产及设置汇率的来源
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+		/// 资产费用的收款账户
+		type FeeCollector: Get<Self::AccountId>;
+		/// 当前区块的出块人
+		type BlockAuthor: Get<Self::AccountId>;
+		/// 出块人分得的资产费用(不含小费)比例
+		#[pallet::constant]
+		type AuthorShare: Get<Permill>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// 已批准的费用资产及汇率:资产数量 = 原生币费用 * 汇率
+	#[pallet::storage]
+	#[pallet::getter(fn fee_rate)]
+	pub type FeeRates<T: Config> = StorageMap<_, Blake2_128Concat, AssetIdOf<T>, FixedU128, OptionQuery>;
+
+	/// 账户选择的费用资产
+	#[pallet::storage]
+	#[pallet::getter(fn fee_asset)]
+	pub type FeeAsset<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, AssetIdOf<T>, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// 批准了费用资产或更新了汇率,资产id及汇率
+		FeeAssetApproved(AssetIdOf<T>, FixedU128),
+		/// 费用资产被移出批准列表
+		FeeAssetRemoved(AssetIdOf<T>),
+		/// 设置了费用资产,为空时改回原生币
+		FeeAssetSet(T::AccountId, Option<AssetIdOf<T>>),
+		/// 用资产支付了交易费用,账户、资产id及实际费用
+		AssetFeePaid(T::AccountId, AssetIdOf<T>, AssetBalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// 资产不存在
+		UnknownAsset,
+		/// 资产不在批准的费用资产列表中
+		AssetNotApproved,
+		/// 汇率不能为零
+		ZeroRate,
+		/// 收款账户持有的资产少于最小余额
+		CollectorBelowMinimum,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// 批准资产用于支付费用,或更新已批准资产的汇率
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn approve_fee_asset(origin: OriginFor<T>, asset_id: AssetIdOf<T>, rate: FixedU128) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			// 不存在的资产最小余额为零
+			ensure!(!T::Assets::minimum_balance(asset_id).is_zero(), Error::<T>::UnknownAsset);
+			ensure!(!rate.is_zero(), Error::<T>::ZeroRate);
+			ensure!(
+				T::Assets::balance(asset_id, &T::FeeCollector::get()) >= T::Assets::minimum_balance(asset_id),
+				Error::<T>::CollectorBelowMinimum
+			);
+			FeeRates::<T>::insert(asset_id, rate);
+			Self::deposit_event(Event::FeeAssetApproved(asset_id, rate));
+			Ok(())
+		}
+
+		/// 把资产移出批准列表,选择该资产的账户之后用原生币支付
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_fee_asset(origin: OriginFor<T>, asset_id: AssetIdOf<T>) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			FeeRates::<T>::take(asset_id).ok_or(Error::<T>::AssetNotApproved)?;
+			Self::deposit_event(Event::FeeAssetRemoved(asset_id));
+			Ok(())
+		}
+
+		/// 选择支付交易费用的资产,只能选择已批准的资产,为空时用原生币支付
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_fee_asset(origin: OriginFor<T>, asset_id: Option<AssetIdOf<T>>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			match asset_id {
+				Some(asset_id) => {
+					ensure!(FeeRates::<T>::contains_key(asset_id), Error::<T>::AssetNotApproved);
+					FeeAsset::<T>::insert(&who, asset_id);
+				},
+				None => FeeAsset::<T>::remove(&who),
+			}
+			Self::deposit_event(Event::FeeAssetSet(who, asset_id));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 按汇率把原生币费用折算为资产数量,不为零的费用至少折算为1个单位;资产未被批准时返回None
+		pub fn to_asset_balance(asset_id: AssetIdOf<T>, fee: BalanceOf<T>) -> Option<AssetBalanceOf<T>> {
+			let rate = FeeRates::<T>::get(asset_id)?;
+			if fee.is_zero() {
+				return Some(Zero::zero())
+			}
+			let amount: AssetBalanceOf<T> = rate.saturating_mul_int(fee.saturated_into::<u128>()).saturated_into();
+			Some(amount.max(One::one()))
+		}
+	}
+}
+
+/// 支持用资产支付费用的 `OnChargeTransaction`,没有选择费用资产的账户交给 `NativeAdapter` 处理
+pub struct AssetConversionAdapter<T>(PhantomData<T>);
+
+impl<T: Config> OnChargeTransaction<T> for AssetConversionAdapter<T> {
+	type Balance = BalanceOf<T>;
+	/// 原生币预扣的费用,或资产id及预扣的资产数量
+	type LiquidityInfo = (
+		<T::NativeAdapter as OnChargeTransaction<T>>::LiquidityInfo,
+		Option<(AssetIdOf<T>, AssetBalanceOf<T>)>,
+	);
+
+	fn withdraw_fee(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::Call,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		let converted = FeeAsset::<T>::get(who)
+			.and_then(|asset_id| Pallet::<T>::to_asset_balance(asset_id, fee).map(|amount| (asset_id, amount)));
+		let (asset_id, amount) = match converted {
+			Some(converted) => converted,
+			None => return Ok((T::NativeAdapter::withdraw_fee(who, call, info, fee, tip)?, None)),
+		};
+		if amount.is_zero() {
+			return Ok(Default::default())
+		}
+		T::Assets::transfer(asset_id, who, &T::FeeCollector::get(), amount, true)
+			.map_err(|_| InvalidTransaction::Payment)?;
+		Ok((Default::default(), Some((asset_id, amount))))
+	}
+
+	fn correct_and_deposit_fee(
+		who: &T::AccountId,
+		dispatch_info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		post_info: &PostDispatchInfoOf<<T as frame_system::Config>::Call>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		let (native, withdrawn) = already_withdrawn;
+		let (asset_id, paid) = match withdrawn {
+			Some(withdrawn) => withdrawn,
+			None =>
+				return T::NativeAdapter::correct_and_deposit_fee(
+					who,
+					dispatch_info,
+					post_info,
+					corrected_fee,
+					tip,
+					native,
+				),
+		};
+		let collector = T::FeeCollector::get();
+		let actual = Pallet::<T>::to_asset_balance(asset_id, corrected_fee).unwrap_or(paid).min(paid);
+		let refund = paid.saturating_sub(actual);
+		if !refund.is_zero() {
+			// 退款失败时多扣的费用留在收款账户
+			let _ = T::Assets::transfer(asset_id, &collector, who, refund, true);
+		}
+
+		// 小费全部及其余费用的一部分归出块人,转账失败时留在收款账户
+		let tip = Pallet::<T>::to_asset_balance(asset_id, tip).unwrap_or_else(Zero::zero).min(actual);
+		let to_author = tip.saturating_add(T::AuthorShare::get() * actual.saturating_sub(tip));
+		if !to_author.is_zero() {
+			let _ = T::Assets::transfer(asset_id, &collector, &T::BlockAuthor::get(), to_author, true);
+		}
+		Pallet::<T>::deposit_event(Event::AssetFeePaid(who.clone(), asset_id, actual));
+		Ok(())
+	}
+}
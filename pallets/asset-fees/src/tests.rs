@@ -0,0 +1,152 @@
+use super::*;
+use crate::mock::{new_test_ext, AssetFees, Assets, Balances, Call, Event as MockEvent, Origin, System, Test};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchClass, DispatchInfo, Pays, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{traits::SignedExtension, FixedPointNumber, FixedU128};
+
+/// 权重 100 的交易费用为 100,折算为资产 7 的 20
+const INFO: DispatchInfo = DispatchInfo { weight: 100, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+#[test]
+fn test_approve_fee_asset() {
+	new_test_ext().execute_with(|| {
+		let rate = FixedU128::saturating_from_rational(1, 2);
+		assert_noop!(
+			AssetFees::approve_fee_asset(Origin::signed(1), 9, rate),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_noop!(AssetFees::approve_fee_asset(Origin::root(), 8, rate), Error::<Test>::UnknownAsset);
+		assert_noop!(
+			AssetFees::approve_fee_asset(Origin::root(), 9, FixedU128::from_inner(0)),
+			Error::<Test>::ZeroRate
+		);
+		// 收款账户需要先持有最小余额
+		assert_noop!(
+			AssetFees::approve_fee_asset(Origin::root(), 9, rate),
+			Error::<Test>::CollectorBelowMinimum
+		);
+		assert_ok!(Assets::mint(Origin::signed(1), 9, 99, 2));
+		assert_ok!(AssetFees::approve_fee_asset(Origin::root(), 9, rate));
+		assert_eq!(AssetFees::fee_rate(9), Some(rate));
+		System::assert_last_event(MockEvent::AssetFees(Event::FeeAssetApproved(9, rate)));
+
+		assert_ok!(AssetFees::remove_fee_asset(Origin::root(), 9));
+		assert_eq!(AssetFees::fee_rate(9), None);
+		assert_noop!(AssetFees::remove_fee_asset(Origin::root(), 9), Error::<Test>::AssetNotApproved);
+	});
+}
+
+#[test]
+fn test_set_fee_asset() {
+	new_test_ext().execute_with(|| {
+		// 只能选择已批准的资产
+		assert_noop!(AssetFees::set_fee_asset(Origin::signed(1), Some(8)), Error::<Test>::AssetNotApproved);
+		assert_noop!(AssetFees::set_fee_asset(Origin::signed(1), Some(9)), Error::<Test>::AssetNotApproved);
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), Some(7)));
+		assert_eq!(AssetFees::fee_asset(1), Some(7));
+		System::assert_last_event(MockEvent::AssetFees(Event::FeeAssetSet(1, Some(7))));
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), None));
+		assert_eq!(AssetFees::fee_asset(1), None);
+	});
+}
+
+#[test]
+fn test_conversion() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(AssetFees::to_asset_balance(7, 100), Some(20));
+		// 不为零的费用至少折算为1个单位
+		assert_eq!(AssetFees::to_asset_balance(7, 4), Some(1));
+		assert_eq!(AssetFees::to_asset_balance(7, 0), Some(0));
+		assert_eq!(AssetFees::to_asset_balance(9, 100), None);
+	});
+}
+
+#[test]
+fn test_pay_fee_in_asset() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), Some(7)));
+		let pre = ChargeTransactionPayment::<Test>::from(0).pre_dispatch(&1, &remark(), &INFO, 0).unwrap();
+		assert_eq!(Assets::balance(7, 1), 980);
+		assert_eq!(Assets::balance(7, 99), 22);
+		assert_eq!(Balances::free_balance(1), 100);
+
+		// 实际权重为 50,多扣的资产退回;实际费用 10 中 20% 归出块人
+		let post_info = PostDispatchInfo { actual_weight: Some(50), pays_fee: Pays::Yes };
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(pre, &INFO, &post_info, 0, &Ok(())));
+		assert_eq!(Assets::balance(7, 1), 990);
+		assert_eq!(Assets::balance(7, 50), 2);
+		assert_eq!(Assets::balance(7, 99), 10);
+		System::assert_last_event(MockEvent::AssetFees(Event::AssetFeePaid(1, 7, 10)));
+	});
+}
+
+#[test]
+fn test_tip_paid_in_asset_goes_to_author() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), Some(7)));
+		// 费用 100 加小费 10,折算为 22
+		let pre = ChargeTransactionPayment::<Test>::from(10).pre_dispatch(&1, &remark(), &INFO, 0).unwrap();
+		assert_eq!(Assets::balance(7, 1), 978);
+
+		// 实际费用 60 折算为 12,其中小费 2 及其余 10 的 20% 归出块人
+		let post_info = PostDispatchInfo { actual_weight: Some(50), pays_fee: Pays::Yes };
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(pre, &INFO, &post_info, 0, &Ok(())));
+		assert_eq!(Assets::balance(7, 1), 988);
+		assert_eq!(Assets::balance(7, 50), 4);
+		assert_eq!(Assets::balance(7, 99), 10);
+	});
+}
+
+#[test]
+fn test_small_fee_is_not_free() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), Some(7)));
+		let info = DispatchInfo { weight: 4, ..INFO };
+		let pre = ChargeTransactionPayment::<Test>::from(0).pre_dispatch(&1, &remark(), &info, 0).unwrap();
+		assert_eq!(Assets::balance(7, 1), 999);
+		let post_info = PostDispatchInfo { actual_weight: None, pays_fee: Pays::Yes };
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(pre, &info, &post_info, 0, &Ok(())));
+		assert_eq!(Assets::balance(7, 99), 3);
+		assert_eq!(Balances::free_balance(1), 100);
+	});
+}
+
+#[test]
+fn test_pay_fee_in_native_currency() {
+	new_test_ext().execute_with(|| {
+		let pre = ChargeTransactionPayment::<Test>::from(0).pre_dispatch(&2, &remark(), &INFO, 0).unwrap();
+		assert_eq!(Balances::free_balance(2), 900);
+		let post_info = PostDispatchInfo { actual_weight: Some(50), pays_fee: Pays::Yes };
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(pre, &INFO, &post_info, 0, &Ok(())));
+		assert_eq!(Balances::free_balance(2), 950);
+	});
+}
+
+#[test]
+fn test_removed_asset_falls_back_to_native_currency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(1), Some(7)));
+		assert_ok!(AssetFees::remove_fee_asset(Origin::root(), 7));
+		let info = DispatchInfo { weight: 50, ..INFO };
+		ChargeTransactionPayment::<Test>::from(0).pre_dispatch(&1, &remark(), &info, 0).unwrap();
+		assert_eq!(Assets::balance(7, 1), 1_000);
+		assert_eq!(Balances::free_balance(1), 50);
+	});
+}
+
+#[test]
+fn test_not_enough_asset_balance() {
+	new_test_ext().execute_with(|| {
+		// 2 没有资产 7,交易无效,也不会改用原生币支付
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(2), Some(7)));
+		assert!(ChargeTransactionPayment::<Test>::from(0).validate(&2, &remark(), &INFO, 0).is_err());
+		assert_eq!(Balances::free_balance(2), 1_000);
+	});
+}
@@ -0,0 +1,129 @@
+//! kitty市场统计的 RPC 接口
+//!
+//! 统计由 `pallet_kitties` 的链下工作机根据链下索引维护,保存在节点的链下本地存储中,
+//! 节点需要以 `--enable-offchain-indexing true` 启动。
+
+use std::marker::PhantomData;
+
+use codec::Decode;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_kitties::analytics::{DailyStats, KittyAnalytics, ANALYTICS_KEY};
+use serde::{Deserialize, Serialize};
+use sp_core::offchain::OffchainStorage;
+use sp_runtime::traits::Saturating;
+
+/// 市场统计
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSummary<Balance> {
+	/// 已统计到的区块高度
+	pub last_block: u64,
+	/// 地板价,没有以原生币在售的kitty时为空
+	pub floor_price: Option<Balance>,
+	/// 以原生币在售的kitty数量
+	pub listed: u32,
+	/// 以资产标价在售的kitty数量
+	pub listed_in_asset: u32,
+	/// 累计创建数量
+	pub total_created: u64,
+	/// 累计孵化数量
+	pub total_bred: u64,
+	/// 累计转移次数
+	pub total_transfers: u64,
+	/// 累计成交笔数
+	pub total_sales: u64,
+	/// 累计成交额
+	pub total_volume: Balance,
+	/// 最近若干天的每日统计
+	pub days: Vec<DailySummary<Balance>>,
+}
+
+/// 每日统计
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummary<Balance> {
+	/// 天数序号
+	pub day: u32,
+	/// 成交额
+	pub volume: Balance,
+	/// 成交笔数
+	pub sales: u32,
+	/// 新创建的kitty数量
+	pub created: u32,
+	/// 孵化的kitty数量
+	pub bred: u32,
+}
+
+impl<Balance> From<DailyStats<Balance>> for DailySummary<Balance> {
+	fn from(stats: DailyStats<Balance>) -> Self {
+		Self {
+			day: stats.day,
+			volume: stats.volume,
+			sales: stats.sales,
+			created: stats.created,
+			bred: stats.bred,
+		}
+	}
+}
+
+/// kitty模块 RPC 方法
+#[rpc]
+pub trait KittiesApi<Balance> {
+	/// 查询链下工作机维护的市场统计,尚未统计时返回空
+	#[rpc(name = "kitties_marketSummary")]
+	fn market_summary(&self) -> Result<Option<MarketSummary<Balance>>>;
+}
+
+/// kitty模块 RPC 实现
+pub struct Kitties<S, KittyIndex, AssetId, Balance> {
+	storage: S,
+	_marker: PhantomData<(KittyIndex, AssetId, Balance)>,
+}
+
+impl<S, KittyIndex, AssetId, Balance> Kitties<S, KittyIndex, AssetId, Balance> {
+	/// 根据节点的链下存储创建 RPC 实例
+	pub fn new(storage: S) -> Self {
+		Self { storage, _marker: Default::default() }
+	}
+}
+
+/// 解码链下存储失败时返回的错误码
+const DECODE_ERROR: i64 = 1;
+
+impl<S, KittyIndex, AssetId, Balance> KittiesApi<Balance>
+	for Kitties<S, KittyIndex, AssetId, Balance>
+where
+	S: OffchainStorage + 'static,
+	KittyIndex: Decode + PartialEq + Send + Sync + 'static,
+	AssetId: Decode + PartialEq + Send + Sync + 'static,
+	Balance: Decode + Copy + Default + Ord + Saturating + Send + Sync + 'static,
+{
+	fn market_summary(&self) -> Result<Option<MarketSummary<Balance>>> {
+		let raw = match self.storage.get(sp_offchain::STORAGE_PREFIX, ANALYTICS_KEY) {
+			Some(raw) => raw,
+			None => return Ok(None),
+		};
+		let stats =
+			KittyAnalytics::<KittyIndex, AssetId, Balance>::decode(&mut &raw[..]).map_err(|e| {
+				RpcError {
+					code: ErrorCode::ServerError(DECODE_ERROR),
+					message: "Unable to decode kitty analytics.".into(),
+					data: Some(format!("{:?}", e).into()),
+				}
+			})?;
+
+		Ok(Some(MarketSummary {
+			last_block: stats.last_block,
+			floor_price: stats.floor_price(),
+			listed: stats.listings.len() as u32,
+			listed_in_asset: stats.asset_listings.len() as u32,
+			total_created: stats.total_created,
+			total_bred: stats.total_bred,
+			total_transfers: stats.total_transfers,
+			total_sales: stats.total_sales,
+			total_volume: stats.total_volume,
+			days: stats.days.into_iter().map(Into::into).collect(),
+		}))
+	}
+}
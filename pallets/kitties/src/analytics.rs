@@ -0,0 +1,173 @@
+//! 基于链下索引的kitty市场统计
+//!
+//! 链上在创建、上架、转移和购买kitty时通过 `sp_io::offchain_index::set` 记录活动,
+//! 链下工作机读取这些记录,在链下本地存储中维护地板价、每日成交量和孵化数量等滚动统计,
+//! 节点通过RPC读取统计结果,不需要额外的索引数据库。
+//!
+//! 链下索引只在节点以 `--enable-offchain-indexing true` 启动时写入。
+
+use codec::{Decode, Encode};
+use sp_runtime::{traits::Saturating, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// 活动记录的键前缀
+pub const ACTIVITY_PREFIX: &[u8] = b"kitties::activity::";
+/// 统计结果在链下本地存储中的键
+pub const ANALYTICS_KEY: &[u8] = b"kitties::analytics";
+/// 保留最近多少天的每日统计
+pub const ANALYTICS_DAYS: usize = 30;
+
+/// 链上记录到链下索引的kitty活动
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum KittyActivity<AccountId, KittyIndex, AssetId, Balance> {
+	/// 创建或孵化了kitty
+	Created { kitty_id: KittyIndex, owner: AccountId, bred: bool },
+	/// 以原生币上架或下架(价格为None)
+	Listed { kitty_id: KittyIndex, price: Option<Balance> },
+	/// 转移kitty
+	Transferred { kitty_id: KittyIndex, from: AccountId, to: AccountId },
+	/// 以原生币购买kitty
+	Bought { kitty_id: KittyIndex, seller: AccountId, buyer: AccountId, price: Balance },
+	/// 以资产标价上架
+	ListedInAsset { kitty_id: KittyIndex, asset_id: AssetId, price: Balance },
+	/// 以资产购买kitty,成交额不计入原生币统计
+	BoughtWithAsset {
+		kitty_id: KittyIndex,
+		seller: AccountId,
+		buyer: AccountId,
+		asset_id: AssetId,
+		price: Balance,
+	},
+}
+
+/// 某一天的统计
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct DailyStats<Balance> {
+	/// 天数序号(区块高度 / 每天区块数)
+	pub day: u32,
+	/// 成交额
+	pub volume: Balance,
+	/// 成交笔数
+	pub sales: u32,
+	/// 新创建的kitty数量(包含孵化)
+	pub created: u32,
+	/// 孵化的kitty数量
+	pub bred: u32,
+}
+
+/// 保存在链下本地存储中的滚动统计
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct KittyAnalytics<KittyIndex, AssetId, Balance> {
+	/// 已处理的最新区块高度
+	pub last_block: u64,
+	/// 此高度及以下的活动记录均已处理或清除(超出补处理范围的区块只清除不统计)
+	pub cleared_block: u64,
+	/// 当前以原生币在售的kitty及价格
+	pub listings: Vec<(KittyIndex, Balance)>,
+	/// 当前以资产标价在售的kitty、资产及价格
+	pub asset_listings: Vec<(KittyIndex, AssetId, Balance)>,
+	/// 最近 `ANALYTICS_DAYS` 天的每日统计,按天数升序
+	pub days: Vec<DailyStats<Balance>>,
+	/// 累计创建数量
+	pub total_created: u64,
+	/// 累计孵化数量
+	pub total_bred: u64,
+	/// 累计转移次数
+	pub total_transfers: u64,
+	/// 累计成交笔数
+	pub total_sales: u64,
+	/// 累计成交额
+	pub total_volume: Balance,
+}
+
+impl<KittyIndex, AssetId, Balance> KittyAnalytics<KittyIndex, AssetId, Balance>
+where
+	KittyIndex: PartialEq,
+	AssetId: PartialEq,
+	Balance: Copy + Default + Ord + Saturating,
+{
+	/// 地板价: 以原生币在售kitty中的最低价格
+	pub fn floor_price(&self) -> Option<Balance> {
+		self.listings.iter().map(|(_, price)| *price).min()
+	}
+
+	/// 资产地板价: 以该资产标价在售kitty中的最低价格
+	pub fn asset_floor_price(&self, asset_id: &AssetId) -> Option<Balance> {
+		self.asset_listings
+			.iter()
+			.filter(|(_, asset, _)| asset == asset_id)
+			.map(|(_, _, price)| *price)
+			.min()
+	}
+
+	/// 将一条活动记录计入统计
+	pub fn apply<AccountId>(
+		&mut self,
+		day: u32,
+		activity: KittyActivity<AccountId, KittyIndex, AssetId, Balance>,
+	) {
+		match activity {
+			KittyActivity::Created { bred, .. } => {
+				self.total_created = self.total_created.saturating_add(1);
+				let stats = self.day_mut(day);
+				stats.created = stats.created.saturating_add(1);
+				if bred {
+					stats.bred = stats.bred.saturating_add(1);
+					self.total_bred = self.total_bred.saturating_add(1);
+				}
+			},
+			KittyActivity::Listed { kitty_id, price } => {
+				self.delist(&kitty_id);
+				if let Some(price) = price {
+					self.listings.push((kitty_id, price));
+				}
+			},
+			KittyActivity::ListedInAsset { kitty_id, asset_id, price } => {
+				self.delist(&kitty_id);
+				self.asset_listings.push((kitty_id, asset_id, price));
+			},
+			KittyActivity::Transferred { .. } => {
+				self.total_transfers = self.total_transfers.saturating_add(1);
+			},
+			KittyActivity::Bought { kitty_id, price, .. } => {
+				self.delist(&kitty_id);
+				self.total_sales = self.total_sales.saturating_add(1);
+				self.total_volume = self.total_volume.saturating_add(price);
+				let stats = self.day_mut(day);
+				stats.sales = stats.sales.saturating_add(1);
+				stats.volume = stats.volume.saturating_add(price);
+			},
+			KittyActivity::BoughtWithAsset { kitty_id, .. } => {
+				self.delist(&kitty_id);
+				self.total_transfers = self.total_transfers.saturating_add(1);
+			},
+		}
+	}
+
+	/// 从原生币及资产在售列表中移除kitty
+	fn delist(&mut self, kitty_id: &KittyIndex) {
+		self.listings.retain(|(id, _)| id != kitty_id);
+		self.asset_listings.retain(|(id, _, _)| id != kitty_id);
+	}
+
+	/// 获取某天的统计,不存在时新建并丢弃超出保留天数的旧数据
+	fn day_mut(&mut self, day: u32) -> &mut DailyStats<Balance> {
+		if self.days.last().map_or(true, |stats| stats.day != day) {
+			self.days.push(DailyStats { day, ..Default::default() });
+			if self.days.len() > ANALYTICS_DAYS {
+				self.days.remove(0);
+			}
+		}
+		self.days.last_mut().expect("pushed above when empty; qed")
+	}
+}
+
+/// 某个区块第index条活动记录的键
+pub fn activity_key<BlockNumber: Encode>(block_number: &BlockNumber, index: u32) -> Vec<u8> {
+	(ACTIVITY_PREFIX, block_number, index).encode()
+}
+
+/// 某个区块活动记录数量上限的键
+pub fn activity_count_key<BlockNumber: Encode>(block_number: &BlockNumber) -> Vec<u8> {
+	(ACTIVITY_PREFIX, b"count", block_number).encode()
+}
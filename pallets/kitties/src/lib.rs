@@ -1,6 +1,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub mod analytics;
+
 #[cfg(test)]
 mod mock;
 
@@ -13,14 +15,23 @@ pub mod pallet {
 	use frame_support::{
 		dispatch::DispatchResult,
 		pallet_prelude::*,
-		traits::{Currency, ExistenceRequirement, Randomness, ReservableCurrency},
+		traits::{tokens::fungibles, Currency, ExistenceRequirement, Randomness, ReservableCurrency},
 		transactional,
 	};
 	use frame_system::pallet_prelude::*;
 	use num_traits::bounds::Bounded;
 	use scale_info::TypeInfo;
 	use sp_io::hashing::blake2_128;
-	use sp_runtime::traits::{AtLeast32Bit, CheckedAdd, One};
+	use sp_runtime::{
+		offchain::storage::{StorageRetrievalError, StorageValueRef},
+		traits::{AtLeast32Bit, CheckedAdd, One, SaturatedConversion, Zero},
+	};
+	use crate::analytics::{self, KittyActivity, KittyAnalytics};
+
+	/// 链下工作机每次最多补处理的区块数
+	const MAX_ANALYTICS_BLOCKS: u64 = 64;
+	/// 链下工作机每次最多清除的被跳过区块数
+	const MAX_CLEARED_BLOCKS: u64 = 1024;
 
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
@@ -30,7 +41,10 @@ pub mod pallet {
 	}
 
 	type AccountOf<T> = <T as frame_system::Config>::AccountId;
-	type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	/// 写入链下索引的kitty活动
+	type ActivityOf<T> =
+		KittyActivity<AccountOf<T>, <T as Config>::KittyIndex, <T as Config>::AssetId, BalanceOf<T>>;
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
@@ -38,9 +52,16 @@ pub mod pallet {
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 		type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
 		type KittyIndex: Parameter + Default + AtLeast32Bit + Copy + Bounded + EncodeLike;
+		/// 可用于标价的资产id
+		type AssetId: Parameter + Copy;
+		/// 多资产,如合作方的稳定币;余额类型与 `Currency` 相同
+		type Assets: fungibles::Transfer<Self::AccountId, AssetId = Self::AssetId, Balance = BalanceOf<Self>>;
 
 		#[pallet::constant]
 		type StakeAmountForKitty: Get<BalanceOf<Self>>;
+		/// 市场统计中每天包含的区块数
+		#[pallet::constant]
+		type BlocksPerDay: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::pallet]
@@ -54,6 +75,10 @@ pub mod pallet {
 		OnSales(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
 		Transferred(T::AccountId, T::AccountId, T::KittyIndex),
 		Bought(T::AccountId, T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
+		/// 以资产标价上架,所有者、kitty索引、资产id及价格
+		OnSalesInAsset(T::AccountId, T::KittyIndex, T::AssetId, Option<BalanceOf<T>>),
+		/// 以资产购买,买家、卖家、kitty索引、资产id及价格
+		BoughtWithAsset(T::AccountId, T::AccountId, T::KittyIndex, T::AssetId, BalanceOf<T>),
 	}
 
 	/// 定义存储
@@ -73,6 +98,15 @@ pub mod pallet {
 	#[pallet::getter(fn kitties_list_for_sale)]
 	pub type ListForSale<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
 
+	/// 上架价格的资产,kitty索引 => 资产id;不在其中的以原生币标价
+	#[pallet::storage]
+	#[pallet::getter(fn listing_asset)]
+	pub type ListingAsset<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AssetId, OptionQuery>;
+
+	/// 本区块已写入链下索引的活动数量,作为活动记录的序号,每个区块开始时清零
+	#[pallet::storage]
+	pub(super) type ActivityCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
 	#[pallet::error]
 	pub enum Error<T> {
 		KittyCntOverflow,
@@ -84,14 +118,31 @@ pub mod pallet {
 		NotEnoughBalanceForStaking,
 	}
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// 清零本区块的活动序号
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			ActivityCount::<T>::kill();
+			T::DbWeight::get().writes(1)
+		}
+
+		/// 将链下索引中的活动记录汇总到市场统计
+		fn offchain_worker(now: T::BlockNumber) {
+			if let Err(e) = Self::update_analytics(now) {
+				log::warn!("更新kitty市场统计失败: {:?}", e);
+			}
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// 创建kitty
-		#[pallet::weight(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 5))]
 		pub fn create(origin: OriginFor<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			let kitty_id = Self::mint(&who, None)?;
 			log::info!("创建了一个kitty,id: {:?}.", kitty_id);
+			Self::index_activity(KittyActivity::Created { kitty_id, owner: who.clone(), bred: false });
 			Self::deposit_event(Event::Created(who, kitty_id));
 			Ok(())
 		}
@@ -102,14 +153,7 @@ pub mod pallet {
 		#[pallet::weight(100)]
 		pub fn transfer(origin: OriginFor<T>, to: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			// 校验是否kitty所有者
-			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
-			// 转移kitty(内部包含kitty存在性验证)
-			Self::transfer_kitty_to(&kitty_id, &to)?;
-			log::info!("账户: {:?} 将id为 {:?} 的kitty, 从自己转移到 账户: {:?}.", who, kitty_id, to);
-			// 转移成功事件
-			Self::deposit_event(Event::Transferred(who, to, kitty_id));
-			Ok(())
+			Self::do_transfer(&who, &to, kitty_id)
 		}
 
 		/// 孵化kitty
@@ -123,6 +167,7 @@ pub mod pallet {
 			// 根据所有者和孵化dna铸造kitty
 			let kitty_id = Self::mint(&who, Some(new_dna))?;
 			log::info!("账户: {:?} 通过id为 {:?} 的kitty和id为 {:?} 的kitty,孵化出id为 {:?} 的kitty.", who, father_kitty_id, mother_kitty_id, kitty_id);
+			Self::index_activity(KittyActivity::Created { kitty_id, owner: who.clone(), bred: true });
 			// 创建成功事件
 			Self::deposit_event(Event::Created(who, kitty_id));
 			Ok(())
@@ -142,22 +187,51 @@ pub mod pallet {
 
 			// 根据售卖和质押要求校验购买账号
 			let ask_price = Self::kitties_list_for_sale(&kitty_id).ok_or(<Error<T>>::KittyNotForSale)?;
+			let asset = Self::listing_asset(&kitty_id);
 			let stake = T::StakeAmountForKitty::get();
 			let free_balance = T::Currency::free_balance(&buyer);
-			ensure!(free_balance > (ask_price + stake), <Error<T>>::NotEnoughBalance);
+			let native_price = if asset.is_some() { Zero::zero() } else { ask_price };
+			ensure!(free_balance > (native_price + stake), <Error<T>>::NotEnoughBalance);
 
-			// 转移资产
+			// 转移资产,以资产标价时用该资产支付
 			let seller = kitty.owner.clone();
-			T::Currency::transfer(&buyer, &seller, ask_price, ExistenceRequirement::KeepAlive)?;
+			match asset {
+				Some(asset) => {
+					T::Assets::transfer(asset, &buyer, &seller, ask_price, true)?;
+				},
+				None => T::Currency::transfer(&buyer, &seller, ask_price, ExistenceRequirement::KeepAlive)?,
+			}
 
 			// 转移kitty
 			Self::transfer_kitty_to(&kitty_id, &buyer)?;
 
 			// 从售卖列表中移除该kitty
 			ListForSale::<T>::remove(kitty_id);
+			ListingAsset::<T>::remove(kitty_id);
 			log::info!("账户: {:?} 花费 {:?} 从 账户: {:?} 购买了id为 {:?} 的kitty.", buyer, ask_price, seller, kitty_id);
 
-			Self::deposit_event(Event::Bought(buyer, seller, kitty_id, Some(ask_price)));
+			match asset {
+				// 市场统计只汇总原生币成交,资产成交按转移计数
+				Some(asset) => {
+					Self::index_activity(KittyActivity::BoughtWithAsset {
+						kitty_id,
+						seller: seller.clone(),
+						buyer: buyer.clone(),
+						asset_id: asset,
+						price: ask_price,
+					});
+					Self::deposit_event(Event::BoughtWithAsset(buyer, seller, kitty_id, asset, ask_price));
+				},
+				None => {
+					Self::index_activity(KittyActivity::Bought {
+						kitty_id,
+						seller: seller.clone(),
+						buyer: buyer.clone(),
+						price: ask_price,
+					});
+					Self::deposit_event(Event::Bought(buyer, seller, kitty_id, Some(ask_price)));
+				},
+			}
 
 			Ok(())
 		}
@@ -168,36 +242,77 @@ pub mod pallet {
 		#[pallet::weight(100)]
 		pub fn sale(origin: OriginFor<T>, kitty_id: T::KittyIndex, price: Option<BalanceOf<T>>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			// kitty必须存在
-			ensure!(<Kitties<T>>::contains_key(&kitty_id), <Error<T>>::KittyNotExist);
-			// 交易所有者权限
-			ensure!(Self::is_kitty_owner(&kitty_id, &sender)?, <Error<T>>::NotKittyOwner);
-
-			// 设置价格并上架
-			ListForSale::<T>::try_mutate(kitty_id, |p| -> DispatchResult {
-				*p = price;
-				Ok(().into())
-			})?;
+			Self::list(&sender, kitty_id, None, price)?;
 
 			log::info!("账户: {:?} 将id为 {:?} 的kitty上架销售，销售价格为 {:?} .", sender, kitty_id, price);
+			Self::index_activity(KittyActivity::Listed { kitty_id, price });
 			Self::deposit_event(Event::OnSales(sender, kitty_id, price));
 
 			Ok(())
 		}
 
+		/// 以资产标价上架销售
+		/// kitty_id: 某个具体kitty的索引
+		/// asset_id: 标价的资产id
+		/// price: 上架销售价格,为空时下架
+		#[pallet::weight(100)]
+		pub fn sale_in_asset(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			asset_id: T::AssetId,
+			price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::list(&sender, kitty_id, Some(asset_id), price)?;
+
+			log::info!("账户: {:?} 将id为 {:?} 的kitty上架销售，销售价格为资产 {:?} {:?} .", sender, kitty_id, asset_id, price);
+			let activity = match price {
+				Some(price) => KittyActivity::ListedInAsset { kitty_id, asset_id, price },
+				None => KittyActivity::Listed { kitty_id, price: None },
+			};
+			Self::index_activity(activity);
+			Self::deposit_event(Event::OnSalesInAsset(sender, kitty_id, asset_id, price));
+
+			Ok(())
+		}
+
 	}
 
 	impl<T: Config> Pallet<T> {
 		/// 随机值
+		///
+		/// 随机源可能在多个区块内保持不变(如BABE按纪元更新),因此同时混入区块号
 		fn random_value(sender: &T::AccountId) -> [u8; 16] {
 			let payload = (
 				T::Randomness::random_seed(),
 				&sender,
+				<frame_system::Pallet<T>>::block_number(),
 				<frame_system::Pallet<T>>::extrinsic_index(),
 			);
 			payload.using_encoded(blake2_128)
 		}
 
+		/// 设置上架价格及标价资产(`None` 为原生币),价格为空时下架
+		fn list(
+			sender: &T::AccountId,
+			kitty_id: T::KittyIndex,
+			asset: Option<T::AssetId>,
+			price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			// kitty必须存在
+			ensure!(<Kitties<T>>::contains_key(&kitty_id), <Error<T>>::KittyNotExist);
+			// 交易所有者权限
+			ensure!(Self::is_kitty_owner(&kitty_id, sender)?, <Error<T>>::NotKittyOwner);
+
+			// 设置价格并上架
+			ListForSale::<T>::insert(kitty_id, price);
+			match asset {
+				Some(asset) if price.is_some() => ListingAsset::<T>::insert(kitty_id, asset),
+				_ => ListingAsset::<T>::remove(kitty_id),
+			}
+			Ok(())
+		}
+
 		/// kitty所有者身份校验
 		/// kitty_id: kitty 索引id
 		/// account_id: 账户id
@@ -243,6 +358,22 @@ pub mod pallet {
 			Ok(kitty_id)
 		}
 
+		/// 所有者转移kitty,供 `transfer` 调用及合约链扩展使用
+		/// who: kitty所有者
+		/// to: 转移到到的账户
+		/// kitty_id: 某个具体kitty的索引
+		pub fn do_transfer(who: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+			// 校验是否kitty所有者
+			ensure!(Self::is_kitty_owner(&kitty_id, who)?, <Error<T>>::NotKittyOwner);
+			// 转移kitty(内部包含kitty存在性验证)
+			Self::transfer_kitty_to(&kitty_id, to)?;
+			log::info!("账户: {:?} 将id为 {:?} 的kitty, 从自己转移到 账户: {:?}.", who, kitty_id, to);
+			Self::index_activity(KittyActivity::Transferred { kitty_id, from: who.clone(), to: to.clone() });
+			// 转移成功事件
+			Self::deposit_event(Event::Transferred(who.clone(), to.clone(), kitty_id));
+			Ok(())
+		}
+
 		/// 转移kitty(成功不返回;失败返回错误信息)
 		/// kitty_id: kitty索引id
 		/// to: 转移kitty到目标账户的accountId
@@ -264,6 +395,85 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// 将kitty活动写入链下索引,键中的序号使用本区块的活动数量保证唯一
+		fn index_activity(activity: ActivityOf<T>) {
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			let index = ActivityCount::<T>::mutate(|count| {
+				let index = *count;
+				*count = count.saturating_add(1);
+				index
+			});
+			sp_io::offchain_index::set(&analytics::activity_key(&block_number, index), &activity.encode());
+			// 记录本区块序号的上限,链下工作机据此遍历活动记录
+			sp_io::offchain_index::set(
+				&analytics::activity_count_key(&block_number),
+				&index.saturating_add(1).encode(),
+			);
+		}
+
+		/// 处理上次之后(最多 `MAX_ANALYTICS_BLOCKS` 个)区块的活动记录,并删除已处理的记录
+		/// 更早的被跳过区块不计入统计,其记录每次最多清除 `MAX_CLEARED_BLOCKS` 个区块
+		/// 同一高度上分叉区块的记录会互相覆盖,统计只反映最后导入的区块
+		pub fn update_analytics(now: T::BlockNumber) -> Result<(), &'static str> {
+			let blocks_per_day = T::BlocksPerDay::get();
+			let store = StorageValueRef::persistent(analytics::ANALYTICS_KEY);
+			store
+				.mutate(
+					|current: Result<
+						Option<KittyAnalytics<T::KittyIndex, T::AssetId, BalanceOf<T>>>,
+						StorageRetrievalError,
+					>| -> Result<_, ()> {
+						// 无法解码时重新统计
+						let mut stats = current.ok().flatten().unwrap_or_default();
+						let end: u64 = now.saturated_into();
+						let start = (stats.last_block + 1).max(end.saturating_sub(MAX_ANALYTICS_BLOCKS - 1));
+
+						let skipped_end =
+							start.saturating_sub(1).min(stats.cleared_block.saturating_add(MAX_CLEARED_BLOCKS));
+						for block in stats.cleared_block.saturating_add(1)..=skipped_end {
+							Self::take_activities(block.saturated_into(), |_| ());
+						}
+						stats.cleared_block = stats.cleared_block.max(skipped_end);
+
+						for block in start..=end {
+							let block_number: T::BlockNumber = block.saturated_into();
+							let day: u32 = if blocks_per_day.is_zero() {
+								0
+							} else {
+								(block_number / blocks_per_day).saturated_into()
+							};
+							Self::take_activities(block_number, |activity| stats.apply(day, activity));
+						}
+
+						// 被跳过的区块都已清除时,已处理的区块同样不再有记录
+						if stats.cleared_block.saturating_add(1) >= start {
+							stats.cleared_block = stats.cleared_block.max(end);
+						}
+						stats.last_block = stats.last_block.max(end);
+						Ok(stats)
+					},
+				)
+				.map(|_| ())
+				.map_err(|_| "analytics storage is locked or failed to update")
+		}
+
+		/// 读取并删除区块在链下索引中的活动记录
+		fn take_activities<F>(block_number: T::BlockNumber, mut f: F)
+		where
+			F: FnMut(ActivityOf<T>),
+		{
+			let mut count_ref = StorageValueRef::persistent(&analytics::activity_count_key(&block_number));
+			let count = count_ref.get::<u32>().ok().flatten().unwrap_or(0);
+			for index in 0..count {
+				let mut activity_ref = StorageValueRef::persistent(&analytics::activity_key(&block_number, index));
+				if let Ok(Some(activity)) = activity_ref.get::<ActivityOf<T>>() {
+					f(activity);
+				}
+				activity_ref.clear();
+			}
+			count_ref.clear();
+		}
+
 		/// 构建dna(成功返回dna;失败返回错误信息)
 		/// who: 构建人accountId
 		/// father_kitty_id: 父kitty索引id
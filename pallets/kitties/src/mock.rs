@@ -22,6 +22,7 @@ frame_support::construct_runtime!(
 		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -75,8 +76,33 @@ impl pallet_balances::Config for Test {
 
 impl pallet_randomness_collective_flip::Config for Test {}
 
+parameter_types! {
+	pub const AssetDeposit: u128 = 0;
+	pub const ApprovalDeposit: u128 = 0;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u128 = 0;
+	pub const MetadataDepositPerByte: u128 = 0;
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const StakeAmountForKitty: u128 = 1_000;
+	pub const BlocksPerDay: u64 = 10;
 }
 
 /// Configure the pallet-kitties in pallets/kitties.
@@ -85,7 +111,10 @@ impl pallet_kitties::Config for Test {
 	type Randomness = RandomnessCollectiveFlip;
 	type Currency = Balances;
 	type KittyIndex = u32;
+	type AssetId = u32;
+	type Assets = Assets;
 	type StakeAmountForKitty = StakeAmountForKitty;
+	type BlocksPerDay = BlocksPerDay;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -1,6 +1,9 @@
 use super::*;
-use crate::mock::{new_test_ext, Event as MockEvent, KittiesModule, Origin, System, Test};
-use frame_support::{assert_noop, assert_ok};
+use crate::mock::{new_test_ext, Assets, Balances, Event as MockEvent, KittiesModule, Origin, System, Test};
+use crate::analytics::{DailyStats, KittyActivity, KittyAnalytics, ANALYTICS_DAYS, ANALYTICS_KEY};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_core::offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt};
+use sp_runtime::offchain::storage::StorageValueRef;
 
 #[test]
 fn test_create() {
@@ -125,6 +128,53 @@ fn test_not_enough_balance() {
 	});
 }
 
+#[test]
+fn test_buy_with_asset() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 7, 1, true, 1));
+		assert_ok!(Assets::mint(Origin::signed(1), 7, 3, 10_000));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		// 原生币不足以支付,但可以用资产支付
+		let price: u128 = 7_500;
+		assert_ok!(KittiesModule::sale_in_asset(Origin::signed(1), 1, 7, Some(price)));
+		assert_eq!(ListingAsset::<Test>::get(1), Some(7));
+		System::assert_has_event(MockEvent::KittiesModule(Event::OnSalesInAsset(1, 1, 7, Some(price))));
+
+		assert_ok!(KittiesModule::buy(Origin::signed(3), 1));
+		assert_eq!(Owner::<Test>::get(1), Some(3));
+		assert_eq!(Assets::balance(7, 1), price);
+		assert_eq!(Assets::balance(7, 3), 2_500);
+		assert_eq!(Balances::free_balance(3), 7_000);
+		assert_eq!(ListForSale::<Test>::get(1), None);
+		assert_eq!(ListingAsset::<Test>::get(1), None);
+		System::assert_has_event(MockEvent::KittiesModule(Event::BoughtWithAsset(3, 1, 1, 7, price)));
+	});
+}
+
+#[test]
+fn test_not_enough_asset_balance() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 7, 1, true, 1));
+		assert_ok!(Assets::mint(Origin::signed(1), 7, 2, 100));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sale_in_asset(Origin::signed(1), 1, 7, Some(1_500)));
+		assert!(KittiesModule::buy(Origin::signed(2), 1).is_err());
+		assert_eq!(Owner::<Test>::get(1), Some(1));
+	});
+}
+
+#[test]
+fn test_relist_in_native_currency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sale_in_asset(Origin::signed(1), 1, 7, Some(1_500)));
+		assert_ok!(KittiesModule::sale(Origin::signed(1), 1, Some(1_500)));
+		assert_eq!(ListingAsset::<Test>::get(1), None);
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 1));
+		System::assert_has_event(MockEvent::KittiesModule(Event::Bought(2, 1, 1, Some(1_500))));
+	});
+}
+
 #[test]
 fn test_not_enough_balance_for_staking() {
 	new_test_ext().execute_with(|| {
@@ -134,3 +184,150 @@ fn test_not_enough_balance_for_staking() {
 		);
 	});
 }
+
+#[test]
+fn test_analytics_apply() {
+	let mut stats = KittyAnalytics::<u32, u32, u128>::default();
+	stats.apply(0, KittyActivity::Created { kitty_id: 1, owner: 1u64, bred: false });
+	stats.apply(0, KittyActivity::Created { kitty_id: 2, owner: 1u64, bred: true });
+	stats.apply(0, KittyActivity::<u64, _, _, _>::Listed { kitty_id: 1, price: Some(300) });
+	stats.apply(0, KittyActivity::<u64, _, _, _>::Listed { kitty_id: 2, price: Some(200) });
+	stats.apply(
+		0,
+		KittyActivity::<u64, _, _, _>::ListedInAsset { kitty_id: 3, asset_id: 7, price: 50 },
+	);
+	assert_eq!(stats.floor_price(), Some(200));
+	assert_eq!(stats.asset_floor_price(&7), Some(50));
+
+	// 改为以资产标价时从原生币在售列表中移除
+	stats.apply(
+		0,
+		KittyActivity::<u64, _, _, _>::ListedInAsset { kitty_id: 1, asset_id: 7, price: 80 },
+	);
+	assert_eq!(stats.floor_price(), Some(200));
+	assert_eq!(stats.asset_listings, vec![(3, 7, 50), (1, 7, 80)]);
+
+	stats.apply(1, KittyActivity::Bought { kitty_id: 2, seller: 1u64, buyer: 2, price: 200 });
+	stats.apply(1, KittyActivity::<u64, _, _, _>::Listed { kitty_id: 1, price: None });
+	stats.apply(
+		1,
+		KittyActivity::BoughtWithAsset {
+			kitty_id: 3,
+			seller: 1u64,
+			buyer: 2,
+			asset_id: 7,
+			price: 50,
+		},
+	);
+	assert_eq!(stats.floor_price(), None);
+	assert_eq!(stats.asset_floor_price(&7), None);
+	assert_eq!(stats.total_transfers, 1);
+	assert_eq!(stats.total_created, 2);
+	assert_eq!(stats.total_bred, 1);
+	assert_eq!(stats.total_volume, 200);
+	assert_eq!(
+		stats.days,
+		vec![
+			DailyStats { day: 0, volume: 0, sales: 0, created: 2, bred: 1 },
+			DailyStats { day: 1, volume: 200, sales: 1, created: 0, bred: 0 },
+		]
+	);
+}
+
+#[test]
+fn test_analytics_keeps_recent_days() {
+	let mut stats = KittyAnalytics::<u32, u32, u128>::default();
+	for day in 0..(ANALYTICS_DAYS as u32 + 5) {
+		stats.apply(day, KittyActivity::Created { kitty_id: day, owner: 1u64, bred: false });
+	}
+	assert_eq!(stats.days.len(), ANALYTICS_DAYS);
+	assert_eq!(stats.days[0].day, 5);
+	assert_eq!(stats.total_created, ANALYTICS_DAYS as u64 + 5);
+}
+
+#[test]
+fn test_offchain_indexed_analytics() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sale(Origin::signed(1), 1, Some(1_500)));
+		assert_ok!(KittiesModule::buy(Origin::signed(2), 1));
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sale_in_asset(Origin::signed(1), 2, 7, Some(900)));
+	});
+	ext.persist_offchain_overlay();
+
+	let (offchain, _state) = TestOffchainExt::with_offchain_db(ext.offchain_db());
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.execute_with(|| {
+		KittiesModule::offchain_worker(1);
+		let stats = StorageValueRef::persistent(ANALYTICS_KEY)
+			.get::<KittyAnalytics<u32, u32, u128>>()
+			.unwrap()
+			.unwrap();
+		assert_eq!(stats.last_block, 1);
+		assert_eq!(stats.total_created, 2);
+		assert_eq!(stats.total_sales, 1);
+		assert_eq!(stats.total_volume, 1_500);
+		assert_eq!(stats.floor_price(), None);
+		// 以资产标价的kitty仍然在售
+		assert_eq!(stats.asset_listings, vec![(2, 7, 900)]);
+		assert_eq!(stats.asset_floor_price(&7), Some(900));
+
+		// 已处理的区块不会重复统计
+		KittiesModule::offchain_worker(1);
+		let again = StorageValueRef::persistent(ANALYTICS_KEY)
+			.get::<KittyAnalytics<u32, u32, u128>>()
+			.unwrap()
+			.unwrap();
+		assert_eq!(again, stats);
+	});
+}
+
+#[test]
+fn test_activity_count_resets_every_block() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		assert_ok!(KittiesModule::sale(Origin::signed(1), 1, Some(1_500)));
+		assert_eq!(ActivityCount::<Test>::get(), 2);
+
+		System::set_block_number(2);
+		KittiesModule::on_initialize(2);
+		assert_eq!(ActivityCount::<Test>::get(), 0);
+	});
+}
+
+#[test]
+fn test_offchain_analytics_clears_skipped_blocks() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+		System::set_block_number(100);
+		KittiesModule::on_initialize(100);
+		assert_ok!(KittiesModule::create(Origin::signed(1)));
+	});
+	ext.persist_offchain_overlay();
+
+	let (offchain, _state) = TestOffchainExt::with_offchain_db(ext.offchain_db());
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.execute_with(|| {
+		KittiesModule::offchain_worker(100);
+		let stats = StorageValueRef::persistent(ANALYTICS_KEY)
+			.get::<KittyAnalytics<u32, u32, u128>>()
+			.unwrap()
+			.unwrap();
+		// 区块1超出补处理范围,不计入统计但记录被清除
+		assert_eq!(stats.total_created, 1);
+		assert_eq!((stats.last_block, stats.cleared_block), (100, 100));
+		assert_eq!(
+			StorageValueRef::persistent(&crate::analytics::activity_count_key(&1u64)).get::<u32>(),
+			Ok(None)
+		);
+		assert_eq!(
+			StorageValueRef::persistent(&crate::analytics::activity_key(&1u64, 0)).get::<Vec<u8>>(),
+			Ok(None)
+		);
+	});
+}
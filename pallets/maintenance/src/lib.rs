@@ -0,0 +1,149 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! 维护模式模块
+//!
+//! 作为runtime的 `BaseCallFilter`,由特权来源暂停整个模块或某个调用,出现事故时可以紧急停止如kitty交易等功能。
+//! 暂停按 `construct_runtime!` 中的模块名及调用名匹配,对批量调用、代理等嵌套调用同样生效;
+//! Root来源不受过滤器限制。治理、sudo及出块所需的模块应列入 `UnpausablePallets`,本模块自身也不能被暂停。
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{CallMetadata, Contains, GetCallMetadata, PalletInfoAccess},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_std::vec::Vec;
+
+	/// 配置接口
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// 事件
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+		/// 暂停及恢复的特权来源
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+		/// 不能被暂停的模块名,如治理、sudo及出块所需的模块
+		type UnpausablePallets: Contains<Vec<u8>>;
+		/// 模块名及调用名的最大长度
+		#[pallet::constant]
+		type MaxNameLength: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// 被暂停的模块
+	#[pallet::storage]
+	pub type PausedPallets<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	/// 被暂停的调用,模块名 => 调用名
+	#[pallet::storage]
+	pub type PausedCalls<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// 暂停了模块
+		PalletPaused(Vec<u8>),
+		/// 恢复了模块
+		PalletResumed(Vec<u8>),
+		/// 暂停了调用,模块名及调用名
+		CallPaused(Vec<u8>, Vec<u8>),
+		/// 恢复了调用,模块名及调用名
+		CallResumed(Vec<u8>, Vec<u8>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// 名称过长
+		NameTooLong,
+		/// 该模块不能被暂停
+		CannotPause,
+		/// 已经暂停
+		AlreadyPaused,
+		/// 没有暂停
+		NotPaused,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// 暂停模块的所有调用
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			Self::ensure_pausable(&pallet_name)?;
+			ensure!(!PausedPallets::<T>::contains_key(&pallet_name), Error::<T>::AlreadyPaused);
+			PausedPallets::<T>::insert(&pallet_name, ());
+			Self::deposit_event(Event::PalletPaused(pallet_name));
+			Ok(())
+		}
+
+		/// 恢复模块,单独暂停的调用保持暂停
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn resume_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(PausedPallets::<T>::contains_key(&pallet_name), Error::<T>::NotPaused);
+			PausedPallets::<T>::remove(&pallet_name);
+			Self::deposit_event(Event::PalletResumed(pallet_name));
+			Ok(())
+		}
+
+		/// 暂停模块的某个调用
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause_call(origin: OriginFor<T>, pallet_name: Vec<u8>, call_name: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			Self::ensure_pausable(&pallet_name)?;
+			ensure!(call_name.len() as u32 <= T::MaxNameLength::get(), Error::<T>::NameTooLong);
+			ensure!(!PausedCalls::<T>::contains_key(&pallet_name, &call_name), Error::<T>::AlreadyPaused);
+			PausedCalls::<T>::insert(&pallet_name, &call_name, ());
+			Self::deposit_event(Event::CallPaused(pallet_name, call_name));
+			Ok(())
+		}
+
+		/// 恢复模块的某个调用
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn resume_call(origin: OriginFor<T>, pallet_name: Vec<u8>, call_name: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(PausedCalls::<T>::contains_key(&pallet_name, &call_name), Error::<T>::NotPaused);
+			PausedCalls::<T>::remove(&pallet_name, &call_name);
+			Self::deposit_event(Event::CallResumed(pallet_name, call_name));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 调用是否被暂停,按模块名及调用名匹配
+		pub fn is_paused(pallet_name: &[u8], call_name: &[u8]) -> bool {
+			PausedPallets::<T>::contains_key(pallet_name) || PausedCalls::<T>::contains_key(pallet_name, call_name)
+		}
+
+		/// 本模块及 `UnpausablePallets` 中的模块不能被暂停
+		fn ensure_pausable(pallet_name: &Vec<u8>) -> DispatchResult {
+			ensure!(pallet_name.len() as u32 <= T::MaxNameLength::get(), Error::<T>::NameTooLong);
+			ensure!(
+				pallet_name.as_slice() != <Self as PalletInfoAccess>::name().as_bytes() &&
+					!T::UnpausablePallets::contains(pallet_name),
+				Error::<T>::CannotPause
+			);
+			Ok(())
+		}
+	}
+
+	/// 作为 `BaseCallFilter`,放行没有被暂停的调用
+	impl<T: Config, C: GetCallMetadata> Contains<C> for Pallet<T> {
+		fn contains(call: &C) -> bool {
+			let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+			!Self::is_paused(pallet_name.as_bytes(), function_name.as_bytes())
+		}
+	}
+}
@@ -0,0 +1,108 @@
+use super::*;
+use crate::mock::{new_test_ext, Call, Event as MockEvent, MaintenanceMode, Origin, System, Test};
+use frame_support::{assert_noop, assert_ok, traits::Contains};
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn remark_with_event() -> Call {
+	Call::System(frame_system::Call::remark_with_event { remark: vec![] })
+}
+
+#[test]
+fn test_pause_pallet() {
+	new_test_ext().execute_with(|| {
+		assert!(MaintenanceMode::contains(&remark()));
+		assert_noop!(
+			MaintenanceMode::pause_pallet(Origin::signed(1), b"System".to_vec()),
+			sp_runtime::traits::BadOrigin
+		);
+
+		assert_ok!(MaintenanceMode::pause_pallet(Origin::root(), b"System".to_vec()));
+		System::assert_last_event(MockEvent::MaintenanceMode(Event::PalletPaused(b"System".to_vec())));
+		assert!(!MaintenanceMode::contains(&remark()));
+		assert!(!MaintenanceMode::contains(&remark_with_event()));
+		assert_noop!(
+			MaintenanceMode::pause_pallet(Origin::root(), b"System".to_vec()),
+			Error::<Test>::AlreadyPaused
+		);
+
+		assert_ok!(MaintenanceMode::resume_pallet(Origin::root(), b"System".to_vec()));
+		System::assert_last_event(MockEvent::MaintenanceMode(Event::PalletResumed(b"System".to_vec())));
+		assert!(MaintenanceMode::contains(&remark()));
+		assert_noop!(
+			MaintenanceMode::resume_pallet(Origin::root(), b"System".to_vec()),
+			Error::<Test>::NotPaused
+		);
+	});
+}
+
+#[test]
+fn test_pause_call() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(MaintenanceMode::pause_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()));
+		System::assert_last_event(MockEvent::MaintenanceMode(Event::CallPaused(
+			b"System".to_vec(),
+			b"remark".to_vec(),
+		)));
+		// 只暂停指定的调用
+		assert!(!MaintenanceMode::contains(&remark()));
+		assert!(MaintenanceMode::contains(&remark_with_event()));
+		assert_noop!(
+			MaintenanceMode::pause_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()),
+			Error::<Test>::AlreadyPaused
+		);
+
+		// 恢复模块不影响单独暂停的调用
+		assert_ok!(MaintenanceMode::pause_pallet(Origin::root(), b"System".to_vec()));
+		assert_ok!(MaintenanceMode::resume_pallet(Origin::root(), b"System".to_vec()));
+		assert!(!MaintenanceMode::contains(&remark()));
+
+		assert_ok!(MaintenanceMode::resume_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()));
+		System::assert_last_event(MockEvent::MaintenanceMode(Event::CallResumed(
+			b"System".to_vec(),
+			b"remark".to_vec(),
+		)));
+		assert!(MaintenanceMode::contains(&remark()));
+		assert_noop!(
+			MaintenanceMode::resume_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()),
+			Error::<Test>::NotPaused
+		);
+	});
+}
+
+#[test]
+fn test_unpausable_pallets() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			MaintenanceMode::pause_pallet(Origin::root(), b"Timestamp".to_vec()),
+			Error::<Test>::CannotPause
+		);
+		assert_noop!(
+			MaintenanceMode::pause_call(Origin::root(), b"Timestamp".to_vec(), b"set".to_vec()),
+			Error::<Test>::CannotPause
+		);
+		// 本模块不能被暂停,否则无法恢复
+		assert_noop!(
+			MaintenanceMode::pause_pallet(Origin::root(), b"MaintenanceMode".to_vec()),
+			Error::<Test>::CannotPause
+		);
+		assert_noop!(
+			MaintenanceMode::pause_pallet(Origin::root(), b"AVeryLongPalletName".to_vec()),
+			Error::<Test>::NameTooLong
+		);
+	});
+}
+
+#[test]
+fn test_filter_applies_to_dispatch() {
+	new_test_ext().execute_with(|| {
+		use frame_support::traits::OriginTrait;
+
+		assert_ok!(MaintenanceMode::pause_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()));
+		assert!(!Origin::signed(1).filter_call(&remark()));
+		// Root来源不受过滤器限制
+		assert!(Origin::root().filter_call(&remark()));
+	});
+}
@@ -1,18 +1,227 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+use frame_support::traits::{Currency, OnUnbalanced};
+use sp_core::crypto::KeyTypeId;
+
+pub mod render;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// 链下工作机签名交易使用的密钥类型,需要通过 `author_insertKey` 插入到节点的keystore中
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"demo");
+
+/// 链下工作机签名使用的sr25519密钥
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct OcwAuthId;
+
+	/// runtime中使用的签名类型
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for OcwAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	/// 测试环境中使用的签名类型
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for OcwAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
-
+	use codec::{Decode, Encode};
+	use crate::render::{self, KittyDna};
 	use frame_support::{
 		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement, Imbalance, OnUnbalanced, ReservableCurrency},
+		transactional, PalletId,
 	};
+	use frame_system::{
+		offchain::{
+			AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendUnsignedTransaction,
+			SignedPayload, Signer, SigningTypes,
+		},
+		pallet_prelude::*,
+	};
+	use lite_json::json::JsonValue;
+	use scale_info::TypeInfo;
+	use sp_runtime::{
+		offchain::{
+			http,
+			storage::StorageValueRef,
+			storage_lock::{BlockAndTime, StorageLock},
+			Duration,
+		},
+		traits::{
+			AccountIdConversion, AtLeast32BitUnsigned, CheckedSub, IdentifyAccount, One, SaturatedConversion,
+			Saturating, Zero,
+		},
+		transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction},
+		Permill,
+	};
+	use sp_core::H256;
+	use sp_std::{str, string::ToString, vec::Vec};
+
+	/// HTTP请求的超时时间(毫秒)
+	const FETCH_TIMEOUT_PERIOD: u64 = 3_000;
+	/// 链下工作机互斥锁的键
+	const LOCK_KEY: &[u8] = b"pallet-ocw::lock";
+	/// 互斥锁最多持有的区块数
+	const LOCK_BLOCK_EXPIRATION: u32 = 3;
+	/// 互斥锁最多持有的时间(毫秒),需覆盖所有数据源的请求时间
+	const LOCK_TIMEOUT_EXPIRATION: u64 = FETCH_TIMEOUT_PERIOD * 4;
+	/// 失败后最长的退避区块数
+	const MAX_BACKOFF_BLOCKS: u64 = 64;
+	/// 提交数字任务的本地存储键
+	pub(crate) const NUMBER_JOB: &[u8] = b"pallet-ocw::job::number";
+	/// 提交价格任务的本地存储键前缀,后接数据源id
+	pub(crate) const PRICE_JOB: &[u8] = b"pallet-ocw::job::price::";
+	/// 渲染kitty图像任务的本地存储键
+	pub(crate) const RENDER_JOB: &[u8] = b"pallet-ocw::job::render";
+
+	/// 数据源id
+	pub type FeedId = u32;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// 罚没保证金的原因
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum SlashReason {
+		/// 报价偏离中位数超过容忍范围
+		Outlier,
+		/// 本轮没有报价
+		Missed,
+		/// 提交的kitty图像哈希与dna不符
+		InvalidRender,
+	}
+
+	/// 预言机数据源
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Feed<T: Config> {
+		/// 数据源地址
+		pub url: BoundedVec<u8, T::MaxUrlLength>,
+		/// 价格在返回JSON中的路径,以 `.` 分隔,如 `data.amount`
+		pub json_path: BoundedVec<u8, T::MaxUrlLength>,
+		/// 提交到链上的价格保留的小数位数
+		pub decimals: u8,
+		/// 每轮报价持续的区块数,每轮结束时取中位数
+		pub interval: T::BlockNumber,
+	}
+
+	/// 链下任务保存在本地存储中的运行记录
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+	pub struct JobState {
+		/// 最近一次运行的区块
+		pub last_run: u64,
+		/// 最近一次成功提交交易的区块
+		pub last_submitted: Option<u64>,
+		/// 连续失败次数
+		pub failures: u32,
+		/// 失败后允许再次运行的区块
+		pub retry_at: u64,
+	}
+
+	/// 通过无签名交易提交、由链下工作机密钥签名的数据
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Payload<Public> {
+		pub number: u64,
+		pub public: Public,
+	}
 
+	impl<T: SigningTypes> SignedPayload<T> for Payload<T::Public> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
+		/// 链下工作机签名使用的密钥
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Call: From<Call<Self>>;
+
+		/// 链上保留的最近提交数字的数量
+		#[pallet::constant]
+		type MaxNumbers: Get<u32>;
+		/// 无签名交易的优先级
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// 管理数据源和预言机白名单的权限
+		type ManagerOrigin: EnsureOrigin<Self::Origin>;
+		/// 数据源的最大数量
+		#[pallet::constant]
+		type MaxFeeds: Get<u32>;
+		/// 数据源地址及JSON路径的最大长度
+		#[pallet::constant]
+		type MaxUrlLength: Get<u32>;
+		/// 白名单中预言机账户的最大数量
+		#[pallet::constant]
+		type MaxOracles: Get<u32>;
+		/// 同一个链下任务两次提交之间至少间隔的区块数
+		#[pallet::constant]
+		type GracePeriod: Get<Self::BlockNumber>;
+
+		/// 预言机保证金及奖励使用的货币
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// 预言机提交报价前需要锁定的保证金
+		#[pallet::constant]
+		type OracleBond: Get<BalanceOf<Self>>;
+		/// 报价在容忍范围内时每轮的奖励
+		#[pallet::constant]
+		type RewardPerRound: Get<BalanceOf<Self>>;
+		/// 报价相对中位数的容忍偏差
+		#[pallet::constant]
+		type Tolerance: Get<Permill>;
+		/// 每个数据源在链上保留最近多少轮的中位数(至少保留最近一轮)
+		#[pallet::constant]
+		type MedianHistory: Get<u32>;
+		/// 报价偏离中位数时罚没的保证金
+		#[pallet::constant]
+		type OutlierSlash: Get<BalanceOf<Self>>;
+		/// 错过一轮报价时罚没的保证金
+		#[pallet::constant]
+		type MissedRoundSlash: Get<BalanceOf<Self>>;
+		/// 罚没资金的去向
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// 奖励池账户的id,预言机奖励从奖励池中支付
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// 质疑成功时罚没资金中奖励给质疑人的比例,其余交给 `OnSlash`
+		#[pallet::constant]
+		type ChallengerReward: Get<Permill>;
+
+		/// kitty索引类型
+		type KittyIndex: Parameter + AtLeast32BitUnsigned + Copy;
+		/// 提供kitty的dna
+		type Kitties: KittyDna<Self::KittyIndex>;
+		/// 每笔交易最多提交的kitty图像哈希数量,也是链下工作机每次渲染的数量
+		#[pallet::constant]
+		type MaxRenders: Get<u32>;
 	}
 
 	#[pallet::pallet]
@@ -20,12 +229,133 @@ pub mod pallet {
 	pub struct Pallet<T>(_);
 
 	/// 定义存储
-	
+	/// 最近提交的数字,超过上限时丢弃最早的数字
+	#[pallet::storage]
+	#[pallet::getter(fn numbers)]
+	pub type Numbers<T: Config> = StorageValue<_, BoundedVec<u64, T::MaxNumbers>, ValueQuery>;
+
+	/// 允许提交价格的预言机账户
+	#[pallet::storage]
+	#[pallet::getter(fn oracles)]
+	pub type Oracles<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxOracles>, ValueQuery>;
+
+	/// 预言机锁定的保证金
+	#[pallet::storage]
+	#[pallet::getter(fn bonds)]
+	pub type Bonds<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// 已注册的数据源,链下工作机每个区块读取以决定获取哪些价格
+	#[pallet::storage]
+	#[pallet::getter(fn feeds)]
+	pub type Feeds<T: Config> = StorageMap<_, Twox64Concat, FeedId, Feed<T>>;
+
+	/// 已注册的数据源数量
+	#[pallet::storage]
+	#[pallet::getter(fn feed_count)]
+	pub type FeedCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// 每个数据源每轮收到的报价(预言机账户, 价格)
+	#[pallet::storage]
+	#[pallet::getter(fn round_prices)]
+	pub type RoundPrices<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		FeedId,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<(T::AccountId, u64), T::MaxOracles>,
+		ValueQuery,
+	>;
+
+	/// 每个数据源最近 `MedianHistory` 轮报价的中位数
+	#[pallet::storage]
+	#[pallet::getter(fn median_of)]
+	pub type Medians<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, FeedId, Twox64Concat, T::BlockNumber, u64>;
+
+	/// 每个数据源最近一轮的聚合价格(轮次, 价格)
+	#[pallet::storage]
+	#[pallet::getter(fn price)]
+	pub type Prices<T: Config> = StorageMap<_, Twox64Concat, FeedId, (T::BlockNumber, u64)>;
+
+	/// kitty图像的哈希及提交账户
+	#[pallet::storage]
+	#[pallet::getter(fn render_of)]
+	pub type Renders<T: Config> = StorageMap<_, Twox64Concat, T::KittyIndex, (H256, T::AccountId)>;
+
+	/// 不大于该索引的kitty均已提交图像哈希
+	#[pallet::storage]
+	#[pallet::getter(fn render_cursor)]
+	pub type RenderCursor<T: Config> = StorageValue<_, T::KittyIndex, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub oracles: Vec<T::AccountId>,
+		/// (id, 地址, JSON路径, 小数位数, 每轮区块数)
+		pub feeds: Vec<(FeedId, Vec<u8>, Vec<u8>, u8, T::BlockNumber)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { oracles: Vec::new(), feeds: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let oracles: BoundedVec<_, _> =
+				self.oracles.clone().try_into().expect("too many genesis oracles");
+			for oracle in oracles.iter() {
+				Pallet::<T>::do_bond(oracle).expect("genesis oracle cannot pay the bond");
+			}
+			Oracles::<T>::put(oracles);
+
+			for (id, url, json_path, decimals, interval) in &self.feeds {
+				let feed = Pallet::<T>::new_feed(url.clone(), json_path.clone(), *decimals, *interval)
+					.expect("invalid genesis feed");
+				Pallet::<T>::insert_feed(*id, feed).expect("too many genesis feeds");
+			}
+		}
+	}
+
 	/// 定义事件
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		NewNumber(Option<T::AccountId>, u64),
+		/// 预言机提交了报价(账户, 数据源, 轮次, 价格)
+		PriceSubmitted(T::AccountId, FeedId, T::BlockNumber, u64),
+		/// 一轮报价结束(数据源, 轮次, 中位数, 报价数量)
+		PriceAggregated(FeedId, T::BlockNumber, u64, u32),
+		/// 预言机白名单已更新(数量)
+		OraclesUpdated(u32),
+		/// 添加了预言机
+		OracleAdded(T::AccountId),
+		/// 移除了预言机
+		OracleRemoved(T::AccountId),
+		/// 注册或更新了数据源
+		FeedRegistered(FeedId),
+		/// 移除了数据源
+		FeedRemoved(FeedId),
+		/// 预言机锁定了保证金(账户, 新增金额)
+		Bonded(T::AccountId, BalanceOf<T>),
+		/// 预言机取回了保证金(账户, 金额)
+		Unbonded(T::AccountId, BalanceOf<T>),
+		/// 预言机获得奖励(账户, 数据源, 轮次, 金额)
+		OracleRewarded(T::AccountId, FeedId, T::BlockNumber, BalanceOf<T>),
+		/// 预言机被罚没保证金(账户, 数据源, 轮次, 金额, 原因)
+		OracleSlashed(T::AccountId, FeedId, T::BlockNumber, BalanceOf<T>, SlashReason),
+		/// 提交了kitty图像哈希(账户, kitty索引, 哈希)
+		RenderSubmitted(T::AccountId, T::KittyIndex, H256),
+		/// 纠正了错误的kitty图像哈希并罚没提交者(kitty索引, 提交者, 正确的哈希, 罚没金额)
+		RenderCorrected(T::KittyIndex, T::AccountId, H256, BalanceOf<T>),
+		/// 质疑人获得罚没资金中的奖励(账户, kitty索引, 金额)
+		ChallengerRewarded(T::AccountId, T::KittyIndex, BalanceOf<T>),
+		/// 奖励池余额不足,没有发放奖励(账户, 数据源, 轮次, 金额)
+		RewardSkipped(T::AccountId, FeedId, T::BlockNumber, BalanceOf<T>),
 	}
 
 	#[pallet::error]
@@ -36,16 +366,729 @@ pub mod pallet {
 		OffchainUnsignedTxError,
 		OffchainUnsignedTxSignedPayloadError,
 		HttpFetchingError,
-		ConvertError
+		ConvertError,
+		/// 不在预言机白名单中
+		NotOracle,
+		/// 本轮已经提交过报价
+		AlreadySubmitted,
+		/// 提交的轮次不是当前轮次
+		WrongRound,
+		/// 预言机数量超过上限
+		TooManyOracles,
+		/// 已经在预言机白名单中
+		AlreadyOracle,
+		/// 数据源不存在
+		FeedNotFound,
+		/// 数据源数量超过上限
+		TooManyFeeds,
+		/// 数据源地址或JSON路径过长
+		UrlTooLong,
+		/// 每轮区块数不能为0
+		InvalidInterval,
+		/// 保证金不足,需要先锁定保证金
+		NotBonded,
+		/// 余额不足以锁定保证金
+		InsufficientBalance,
+		/// 仍在预言机白名单中,不能取回保证金
+		StillOracle,
+		/// kitty不存在
+		KittyNotFound,
+		/// kitty图像哈希已提交
+		AlreadyRendered,
+		/// kitty图像哈希尚未提交
+		RenderNotFound,
+		/// 一次提交的kitty图像哈希过多
+		TooManyRenders,
+		/// kitty图像哈希正确,质疑无效
+		RenderIsValid,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// 数据源新一轮开始时聚合上一轮的报价
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut reads = 0u64;
+			let mut finalized = 0u64;
+			for (feed_id, feed) in Feeds::<T>::iter() {
+				reads += 1;
+				if !(now % feed.interval).is_zero() {
+					continue
+				}
+				let round = now / feed.interval;
+				if !round.is_zero() {
+					Self::finalize_round(feed_id, round - 1u32.into());
+					finalized += 1;
+				}
+			}
+			// 每个数据源结算时读写所有预言机的余额和保证金
+			let oracles = Self::oracles().len() as u64;
+			T::DbWeight::get().reads_writes(
+				reads + finalized * (2 + oracles * 2),
+				finalized * (4 + oracles * 2),
+			)
+		}
+
+		/// 链下工作机入口,每导入一个区块执行一次
+		/// 同一时间只允许一个链下工作机运行,上一次运行未结束时直接跳过
+		fn offchain_worker(block_number: T::BlockNumber) {
+			let mut lock = StorageLock::<BlockAndTime<frame_system::Pallet<T>>>::with_block_and_time_deadline(
+				LOCK_KEY,
+				LOCK_BLOCK_EXPIRATION,
+				Duration::from_millis(LOCK_TIMEOUT_EXPIRATION),
+			);
+			let mut guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::info!("上一次链下任务尚未结束,跳过区块 {:?}.", block_number);
+					return
+				},
+			};
+
+			let now: u64 = block_number.saturated_into();
+			let grace: u64 = T::GracePeriod::get().saturated_into();
+
+			// 偶数区块提交签名交易,奇数区块提交带签名数据的无签名交易
+			Self::run_job(
+				NUMBER_JOB,
+				now,
+				|state| state.last_submitted.map_or(true, |last| now >= last.saturating_add(grace)),
+				|| {
+					if now % 2 == 0 {
+						Self::offchain_signed_tx(now)
+					} else {
+						Self::offchain_unsigned_tx_signed_payload(now)
+					}
+				},
+			);
+
+			// 每个数据源在每轮第一个区块提交一次价格,失败后按退避时间在本轮内重试
+			for (feed_id, feed) in Feeds::<T>::iter() {
+				let interval: u64 = feed.interval.saturated_into();
+				let round = now / interval;
+				Self::run_job(
+					&Self::price_job_key(feed_id),
+					now,
+					|state| {
+						(now % interval == 0 || state.failures > 0) &&
+							state.last_submitted.map_or(true, |last| {
+								last / interval < round && now >= last.saturating_add(grace)
+							})
+					},
+					|| Self::fetch_and_submit_price(feed_id, &feed, round.saturated_into()),
+				);
+				// 每个数据源的请求都有超时,处理完一个后延长锁的有效期
+				let _ = guard.extend_lock();
+			}
+
+			// 渲染新创建的kitty并提交图像哈希
+			let renders = Self::pending_renders();
+			if !renders.is_empty() {
+				Self::run_job(
+					RENDER_JOB,
+					now,
+					|state| state.last_submitted.map_or(true, |last| now >= last.saturating_add(grace)),
+					|| Self::submit_renders(renders),
+				);
+			}
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		/// 只接受由预言机白名单中的链下工作机密钥签名的数据,每个密钥每个区块只能提交一次
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::submit_number_unsigned_with_signed_payload { payload, signature } = call {
+				if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+					return InvalidTransaction::BadProof.into()
+				}
+				let authority = payload.public.clone().into_account();
+				if !Self::oracles().contains(&authority) {
+					return InvalidTransaction::BadSigner.into()
+				}
+				let block_number = <frame_system::Pallet<T>>::block_number();
+				ValidTransaction::with_tag_prefix("pallet-ocw")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((block_number, authority))
+					.longevity(5)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		
+		/// 链下工作机通过签名交易提交数字
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn submit_number_signed(origin: OriginFor<T>, number: u64) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			log::info!("账户: {:?} 提交了数字 {}.", who, number);
+			Self::append_number(Some(who), number);
+			Ok(())
+		}
+
+		/// 链下工作机通过带签名数据的无签名交易提交数字,签名在 `validate_unsigned` 中校验
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn submit_number_unsigned_with_signed_payload(
+			origin: OriginFor<T>,
+			payload: Payload<T::Public>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			log::info!("链下工作机通过无签名交易提交了数字 {}.", payload.number);
+			Self::append_number(None, payload.number);
+			Ok(())
+		}
+
+		/// 预言机提交数据源当前轮次的价格(按数据源的小数位数),每轮每个预言机只能提交一次
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn submit_price(
+			origin: OriginFor<T>,
+			feed_id: FeedId,
+			round: T::BlockNumber,
+			price: u64,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::oracles().contains(&who), Error::<T>::NotOracle);
+			ensure!(Self::bonds(&who) >= T::OracleBond::get(), Error::<T>::NotBonded);
+			let feed = Self::feeds(feed_id).ok_or(Error::<T>::FeedNotFound)?;
+			ensure!(round == Self::current_round(feed.interval), Error::<T>::WrongRound);
+
+			RoundPrices::<T>::try_mutate(feed_id, round, |prices| -> DispatchResult {
+				ensure!(!prices.iter().any(|(oracle, _)| oracle == &who), Error::<T>::AlreadySubmitted);
+				prices.try_push((who.clone(), price)).map_err(|_| Error::<T>::TooManyOracles)?;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::PriceSubmitted(who, feed_id, round, price));
+			Ok(())
+		}
+
+		/// 锁定保证金,被罚没后再次调用可补足到 `OracleBond`
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn bond(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_bond(&who)?;
+			Ok(())
+		}
+
+		/// 取回全部保证金,需要先被移出预言机白名单
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn unbond(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!Self::oracles().contains(&who), Error::<T>::StillOracle);
+			let bond = Bonds::<T>::take(&who);
+			ensure!(!bond.is_zero(), Error::<T>::NotBonded);
+			T::Currency::unreserve(&who, bond);
+			Self::deposit_event(Event::Unbonded(who, bond));
+			Ok(())
+		}
+
+		/// 预言机提交kitty图像哈希
+		/// renders: (kitty索引, 图像哈希)
+		#[transactional]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3 + 2 * renders.len() as u64, 1 + renders.len() as u64))]
+		pub fn submit_render_hashes(origin: OriginFor<T>, renders: Vec<(T::KittyIndex, H256)>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::oracles().contains(&who), Error::<T>::NotOracle);
+			ensure!(Self::bonds(&who) >= T::OracleBond::get(), Error::<T>::NotBonded);
+			ensure!(renders.len() as u32 <= T::MaxRenders::get(), Error::<T>::TooManyRenders);
+
+			for (kitty_id, hash) in renders {
+				ensure!(T::Kitties::dna(kitty_id).is_some(), Error::<T>::KittyNotFound);
+				ensure!(!Renders::<T>::contains_key(kitty_id), Error::<T>::AlreadyRendered);
+				Renders::<T>::insert(kitty_id, (hash, who.clone()));
+				Self::deposit_event(Event::RenderSubmitted(who.clone(), kitty_id, hash));
+			}
+
+			let mut cursor = Self::render_cursor();
+			while Renders::<T>::contains_key(cursor + One::one()) {
+				cursor += One::one();
+			}
+			RenderCursor::<T>::put(cursor);
+			Ok(())
+		}
 
+		/// 质疑kitty图像哈希,链上重新渲染校验
+		/// 哈希错误时替换为正确的哈希,并罚没提交者的全部保证金,其中 `ChallengerReward` 比例奖励给质疑人
+		#[pallet::weight(50_000_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn challenge_render(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (hash, submitter) = Self::render_of(kitty_id).ok_or(Error::<T>::RenderNotFound)?;
+			let dna = T::Kitties::dna(kitty_id).ok_or(Error::<T>::KittyNotFound)?;
+			let expected = render::render_hash(&dna);
+			ensure!(hash != expected, Error::<T>::RenderIsValid);
+
+			Renders::<T>::insert(kitty_id, (expected, who.clone()));
+			let imbalance = Self::slash_imbalance(&submitter, T::OracleBond::get());
+			let slashed = imbalance.peek();
+			let (reward, rest) = imbalance.split(T::ChallengerReward::get() * slashed);
+			let rewarded = reward.peek();
+			T::Currency::resolve_creating(&who, reward);
+			T::OnSlash::on_unbalanced(rest);
+			Self::deposit_event(Event::RenderCorrected(kitty_id, submitter, expected, slashed));
+			if !rewarded.is_zero() {
+				Self::deposit_event(Event::ChallengerRewarded(who, kitty_id, rewarded));
+			}
+			Ok(())
+		}
+
+		/// 注册数据源,id已存在时更新该数据源;每轮区块数改变时轮次编号随之改变,清除旧的报价和中位数
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 4 + T::MedianHistory::get() as u64))]
+		pub fn register_feed(
+			origin: OriginFor<T>,
+			feed_id: FeedId,
+			url: Vec<u8>,
+			json_path: Vec<u8>,
+			decimals: u8,
+			interval: T::BlockNumber,
+		) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			let feed = Self::new_feed(url, json_path, decimals, interval)?;
+			let interval_changed =
+				Self::feeds(feed_id).map_or(false, |old| old.interval != interval);
+			Self::insert_feed(feed_id, feed)?;
+			if interval_changed {
+				Self::clear_rounds(feed_id);
+			}
+			Self::deposit_event(Event::FeedRegistered(feed_id));
+			Ok(())
+		}
+
+		/// 移除数据源及其报价、中位数和聚合价格
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 5 + T::MedianHistory::get() as u64))]
+		pub fn remove_feed(origin: OriginFor<T>, feed_id: FeedId) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			ensure!(Feeds::<T>::contains_key(feed_id), Error::<T>::FeedNotFound);
+			Feeds::<T>::remove(feed_id);
+			FeedCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+			Self::clear_rounds(feed_id);
+			Prices::<T>::remove(feed_id);
+			Self::deposit_event(Event::FeedRemoved(feed_id));
+			Ok(())
+		}
+
+		/// 添加预言机
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Oracles::<T>::try_mutate(|oracles| -> DispatchResult {
+				ensure!(!oracles.contains(&who), Error::<T>::AlreadyOracle);
+				oracles.try_push(who.clone()).map_err(|_| Error::<T>::TooManyOracles)?;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::OracleAdded(who));
+			Ok(())
+		}
+
+		/// 移除预言机
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Oracles::<T>::try_mutate(|oracles| -> DispatchResult {
+				let index = oracles.iter().position(|o| o == &who).ok_or(Error::<T>::NotOracle)?;
+				oracles.remove(index);
+				Ok(())
+			})?;
+			Self::deposit_event(Event::OracleRemoved(who));
+			Ok(())
+		}
+
+		/// 设置预言机白名单
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_oracles(origin: OriginFor<T>, oracles: Vec<T::AccountId>) -> DispatchResult {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			let oracles: BoundedVec<_, T::MaxOracles> =
+				oracles.try_into().map_err(|_| Error::<T>::TooManyOracles)?;
+			let count = oracles.len() as u32;
+			Oracles::<T>::put(oracles);
+			Self::deposit_event(Event::OraclesUpdated(count));
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
+		/// 保存提交的数字,超过上限时丢弃最早的数字
+		fn append_number(who: Option<T::AccountId>, number: u64) {
+			Numbers::<T>::mutate(|numbers| {
+				if numbers.len() as u32 >= T::MaxNumbers::get() && !numbers.is_empty() {
+					numbers.remove(0);
+				}
+				// 上面已经腾出空间,MaxNumbers为0时忽略
+				let _ = numbers.try_push(number);
+			});
+			Self::deposit_event(Event::NewNumber(who, number));
+		}
+
+		/// 运行链下任务并在本地存储中记录结果
+		/// 任务处于退避期或 `due` 返回false时跳过,失败后按指数退避推迟下次运行
+		fn run_job(
+			key: &[u8],
+			now: u64,
+			due: impl FnOnce(&JobState) -> bool,
+			job: impl FnOnce() -> Result<(), Error<T>>,
+		) {
+			// 已持有互斥锁,可以直接读写
+			let store = StorageValueRef::persistent(key);
+			let mut state = store.get::<JobState>().ok().flatten().unwrap_or_default();
+			if now < state.retry_at || !due(&state) {
+				return
+			}
+
+			state.last_run = now;
+			match job() {
+				Ok(()) => {
+					state.last_submitted = Some(now);
+					state.failures = 0;
+					state.retry_at = 0;
+				},
+				Err(e) => {
+					state.failures = state.failures.saturating_add(1);
+					state.retry_at = now.saturating_add(Self::backoff(state.failures));
+					log::error!(
+						"链下任务 {:?} 第 {} 次失败: {:?}, 区块 {} 后重试.",
+						str::from_utf8(key).unwrap_or_default(),
+						state.failures,
+						e,
+						state.retry_at,
+					);
+				},
+			}
+			store.set(&state);
+		}
+
+		/// 数据源价格任务的本地存储键
+		pub(crate) fn price_job_key(feed_id: FeedId) -> Vec<u8> {
+			let mut key = PRICE_JOB.to_vec();
+			key.extend_from_slice(feed_id.to_string().as_bytes());
+			key
+		}
+
+		/// 连续失败后的退避区块数: 2, 4, 8 ... 最多 `MAX_BACKOFF_BLOCKS`
+		pub(crate) fn backoff(failures: u32) -> u64 {
+			1u64.checked_shl(failures).unwrap_or(u64::MAX).min(MAX_BACKOFF_BLOCKS)
+		}
+
+		/// 校验并构建数据源
+		fn new_feed(
+			url: Vec<u8>,
+			json_path: Vec<u8>,
+			decimals: u8,
+			interval: T::BlockNumber,
+		) -> Result<Feed<T>, Error<T>> {
+			ensure!(!interval.is_zero(), Error::<T>::InvalidInterval);
+			Ok(Feed {
+				url: url.try_into().map_err(|_| Error::<T>::UrlTooLong)?,
+				json_path: json_path.try_into().map_err(|_| Error::<T>::UrlTooLong)?,
+				decimals,
+				interval,
+			})
+		}
+
+		/// 保存数据源,新增时检查数量上限
+		fn insert_feed(feed_id: FeedId, feed: Feed<T>) -> Result<(), Error<T>> {
+			if !Feeds::<T>::contains_key(feed_id) {
+				let count = Self::feed_count();
+				ensure!(count < T::MaxFeeds::get(), Error::<T>::TooManyFeeds);
+				FeedCount::<T>::put(count + 1);
+			}
+			Feeds::<T>::insert(feed_id, feed);
+			Ok(())
+		}
+
+		/// 清除数据源所有轮次的报价和中位数
+		/// 每个数据源同时最多有两轮未结束的报价,中位数最多保留 `MedianHistory` 轮
+		fn clear_rounds(feed_id: FeedId) {
+			let _ = RoundPrices::<T>::remove_prefix(feed_id, None);
+			let _ = Medians::<T>::remove_prefix(feed_id, None);
+		}
+
+		/// 每轮区块数为interval时的当前报价轮次
+		pub fn current_round(interval: T::BlockNumber) -> T::BlockNumber {
+			if interval.is_zero() {
+				return Zero::zero()
+			}
+			<frame_system::Pallet<T>>::block_number() / interval
+		}
+
+		/// 结束数据源的一轮报价,取中位数作为该轮价格并结算预言机的奖励和罚没
+		pub(crate) fn finalize_round(feed_id: FeedId, round: T::BlockNumber) {
+			let prices = RoundPrices::<T>::take(feed_id, round);
+			let mut values: Vec<u64> = prices.iter().map(|(_, price)| *price).collect();
+			let count = values.len() as u32;
+			if let Some(median) = Self::median(&mut values) {
+				// 轮次连续结算,每次只需删除刚超出保留范围的一轮
+				if let Some(expired) = round.checked_sub(&T::MedianHistory::get().into()) {
+					Medians::<T>::remove(feed_id, expired);
+				}
+				Medians::<T>::insert(feed_id, round, median);
+				Prices::<T>::insert(feed_id, (round, median));
+				Self::deposit_event(Event::PriceAggregated(feed_id, round, median, count));
+				Self::settle_round(feed_id, round, median, &prices);
+			}
+		}
+
+		/// 奖励报价在容忍范围内的预言机,罚没偏离中位数或没有报价的预言机
+		/// 没有任何报价的轮次视为数据源故障,不做罚没
+		fn settle_round(
+			feed_id: FeedId,
+			round: T::BlockNumber,
+			median: u64,
+			prices: &[(T::AccountId, u64)],
+		) {
+			let tolerance = T::Tolerance::get() * median;
+			for (oracle, price) in prices {
+				let deviation = if *price > median { price - median } else { median - price };
+				if deviation <= tolerance {
+					let reward = T::RewardPerRound::get();
+					// 奖励从奖励池支付,不增发
+					let event = match T::Currency::transfer(
+						&Self::reward_pot(),
+						oracle,
+						reward,
+						ExistenceRequirement::KeepAlive,
+					) {
+						Ok(()) => Event::OracleRewarded(oracle.clone(), feed_id, round, reward),
+						Err(_) => Event::RewardSkipped(oracle.clone(), feed_id, round, reward),
+					};
+					Self::deposit_event(event);
+				} else {
+					Self::slash_and_report(oracle, feed_id, round, T::OutlierSlash::get(), SlashReason::Outlier);
+				}
+			}
+
+			for oracle in Self::oracles().iter() {
+				if !prices.iter().any(|(who, _)| who == oracle) && !Self::bonds(oracle).is_zero() {
+					Self::slash_and_report(oracle, feed_id, round, T::MissedRoundSlash::get(), SlashReason::Missed);
+				}
+			}
+		}
+
+		/// 罚没报价相关的保证金并发出事件
+		fn slash_and_report(
+			who: &T::AccountId,
+			feed_id: FeedId,
+			round: T::BlockNumber,
+			amount: BalanceOf<T>,
+			reason: SlashReason,
+		) {
+			let slashed = Self::slash(who, amount);
+			if !slashed.is_zero() {
+				Self::deposit_event(Event::OracleSlashed(who.clone(), feed_id, round, slashed, reason));
+			}
+		}
+
+		/// 奖励池账户
+		pub fn reward_pot() -> T::AccountId {
+			T::PalletId::get().into_account()
+		}
+
+		/// 从保证金中罚没并交给 `OnSlash`,最多罚没全部保证金,返回实际罚没的金额
+		pub(crate) fn slash(who: &T::AccountId, amount: BalanceOf<T>) -> BalanceOf<T> {
+			let imbalance = Self::slash_imbalance(who, amount);
+			let slashed = imbalance.peek();
+			T::OnSlash::on_unbalanced(imbalance);
+			slashed
+		}
+
+		/// 从保证金中罚没,最多罚没全部保证金,返回罚没的资金
+		fn slash_imbalance(who: &T::AccountId, amount: BalanceOf<T>) -> NegativeImbalanceOf<T> {
+			let amount = amount.min(Self::bonds(who));
+			if amount.is_zero() {
+				return NegativeImbalanceOf::<T>::zero()
+			}
+			let (imbalance, _) = T::Currency::slash_reserved(who, amount);
+			Bonds::<T>::mutate(who, |bond| *bond = bond.saturating_sub(imbalance.peek()));
+			imbalance
+		}
+
+		/// 锁定保证金,补足到 `OracleBond`
+		fn do_bond(who: &T::AccountId) -> Result<(), Error<T>> {
+			let bond = Self::bonds(who);
+			let required = T::OracleBond::get().saturating_sub(bond);
+			if required.is_zero() {
+				return Ok(())
+			}
+			T::Currency::reserve(who, required).map_err(|_| Error::<T>::InsufficientBalance)?;
+			Bonds::<T>::insert(who, bond.saturating_add(required));
+			Self::deposit_event(Event::Bonded(who.clone(), required));
+			Ok(())
+		}
+
+		/// 计算中位数,数量为偶数时取中间两个数的平均值
+		pub(crate) fn median(values: &mut [u64]) -> Option<u64> {
+			if values.is_empty() {
+				return None
+			}
+			values.sort_unstable();
+			let mid = values.len() / 2;
+			if values.len() % 2 == 0 {
+				Some(((values[mid - 1] as u128 + values[mid] as u128) / 2) as u64)
+			} else {
+				Some(values[mid])
+			}
+		}
+
+		/// 获取数据源的价格并以签名交易提交
+		fn fetch_and_submit_price(feed_id: FeedId, feed: &Feed<T>, round: T::BlockNumber) -> Result<(), Error<T>> {
+			let url = str::from_utf8(&feed.url).map_err(|_| Error::<T>::ConvertError)?;
+			let json_path = str::from_utf8(&feed.json_path).map_err(|_| Error::<T>::ConvertError)?;
+			let price = Self::fetch_price(url, json_path, feed.decimals)?;
+
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			match signer.send_signed_transaction(|_account| Call::submit_price { feed_id, round, price }) {
+				Some((_, Ok(()))) => Ok(()),
+				Some((_, Err(()))) => Err(Error::<T>::OffchainSignedTxError),
+				None => Err(Error::<T>::NoLocalAcctForSigning),
+			}
+		}
+
+		/// 渲染游标之后尚未提交图像哈希的kitty,最多 `MaxRenders` 个
+		pub(crate) fn pending_renders() -> Vec<(T::KittyIndex, H256)> {
+			let last = match T::Kitties::last_kitty_id() {
+				Some(last) => last,
+				None => return Vec::new(),
+			};
+			let mut renders = Vec::new();
+			let mut kitty_id = Self::render_cursor();
+			for _ in 0..T::MaxRenders::get() {
+				if kitty_id >= last {
+					break
+				}
+				kitty_id += One::one();
+				if Renders::<T>::contains_key(kitty_id) {
+					continue
+				}
+				if let Some(dna) = T::Kitties::dna(kitty_id) {
+					renders.push((kitty_id, render::render_hash(&dna)));
+				}
+			}
+			renders
+		}
+
+		/// 提交kitty图像哈希
+		fn submit_renders(renders: Vec<(T::KittyIndex, H256)>) -> Result<(), Error<T>> {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			match signer.send_signed_transaction(|_account| Call::submit_render_hashes { renders: renders.clone() }) {
+				Some((_, Ok(()))) => Ok(()),
+				Some((_, Err(()))) => Err(Error::<T>::OffchainSignedTxError),
+				None => Err(Error::<T>::NoLocalAcctForSigning),
+			}
+		}
+
+		/// 通过HTTP获取价格
+		pub(crate) fn fetch_price(url: &str, json_path: &str, decimals: u8) -> Result<u64, Error<T>> {
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_PERIOD));
+			let pending = http::Request::get(url)
+				.deadline(deadline)
+				.send()
+				.map_err(|_| Error::<T>::HttpFetchingError)?;
+			let response = pending
+				.try_wait(deadline)
+				.map_err(|_| Error::<T>::HttpFetchingError)?
+				.map_err(|_| Error::<T>::HttpFetchingError)?;
+			if response.code != 200 {
+				log::warn!("HTTP请求返回了异常状态码: {}", response.code);
+				return Err(Error::<T>::HttpFetchingError)
+			}
+
+			let body = response.body().collect::<Vec<u8>>();
+			let body = str::from_utf8(&body).map_err(|_| Error::<T>::ConvertError)?;
+			Self::parse_price(body, json_path, decimals).ok_or(Error::<T>::ConvertError)
+		}
+
+		/// 按JSON路径取出价格,返回保留decimals位小数的定点数
+		/// 路径中的每一段为对象的键或数组下标,价格可以是数字或数字字符串,如 `"12.34"`
+		pub(crate) fn parse_price(body: &str, json_path: &str, decimals: u8) -> Option<u64> {
+			let mut value = lite_json::parse_json(body).ok()?;
+			for segment in json_path.split('.').filter(|s| !s.is_empty()) {
+				value = match value {
+					JsonValue::Object(obj) =>
+						obj.into_iter().find(|(k, _)| k.iter().copied().eq(segment.chars()))?.1,
+					JsonValue::Array(array) => array.into_iter().nth(segment.parse().ok()?)?,
+					_ => return None,
+				};
+			}
+
+			let (integer, fraction, fraction_length) = match value {
+				JsonValue::Number(number) => {
+					if number.negative || number.exponent != 0 {
+						return None
+					}
+					(number.integer, number.fraction, number.fraction_length)
+				},
+				JsonValue::String(chars) => Self::parse_decimal(&chars)?,
+				_ => return None,
+			};
+			Self::to_fixed_point(integer, fraction, fraction_length, decimals)
+		}
+
+		/// 解析 `12.34` 形式的十进制字符串,返回(整数部分, 小数部分, 小数位数)
+		fn parse_decimal(chars: &[char]) -> Option<(u64, u64, u32)> {
+			let mut parts = chars.split(|c| *c == '.');
+			let integer = parts.next()?;
+			let fraction = parts.next().unwrap_or(&[]);
+			if integer.is_empty() || parts.next().is_some() {
+				return None
+			}
+			let digits = |digits: &[char]| -> Option<u64> {
+				digits.iter().try_fold(0u64, |acc, c| acc.checked_mul(10)?.checked_add(c.to_digit(10)? as u64))
+			};
+			Some((digits(integer)?, digits(fraction)?, fraction.len() as u32))
+		}
+
+		/// 将小数换算为保留decimals位小数的定点数,多余的小数位直接截断
+		fn to_fixed_point(integer: u64, fraction: u64, fraction_length: u32, decimals: u8) -> Option<u64> {
+			let decimals = decimals as u32;
+			let fraction = if fraction_length > decimals {
+				fraction / 10u64.checked_pow(fraction_length - decimals)?
+			} else {
+				fraction.checked_mul(10u64.checked_pow(decimals - fraction_length)?)?
+			};
+			integer.checked_mul(10u64.checked_pow(decimals)?)?.checked_add(fraction)
+		}
+
+		/// 使用keystore中任意一个链下工作机密钥提交签名交易
+		pub(crate) fn offchain_signed_tx(number: u64) -> Result<(), Error<T>> {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			let result = signer.send_signed_transaction(|_account| Call::submit_number_signed { number });
+
+			match result {
+				Some((account, Ok(()))) => {
+					log::info!("账户: {:?} 提交签名交易成功, 数字: {}.", account.id, number);
+					Ok(())
+				},
+				Some((_, Err(()))) => Err(Error::<T>::OffchainSignedTxError),
+				None => Err(Error::<T>::NoLocalAcctForSigning),
+			}
+		}
+
+		/// 使用keystore中任意一个链下工作机密钥签名数据,并通过无签名交易提交
+		pub(crate) fn offchain_unsigned_tx_signed_payload(number: u64) -> Result<(), Error<T>> {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			let result = signer.send_unsigned_transaction(
+				|account| Payload { number, public: account.public.clone() },
+				|payload, signature| Call::submit_number_unsigned_with_signed_payload { payload, signature },
+			);
+
+			match result {
+				Some((_, Ok(()))) => Ok(()),
+				Some((_, Err(()))) => Err(Error::<T>::OffchainUnsignedTxSignedPayloadError),
+				None => Err(Error::<T>::NoLocalAcctForSigning),
+			}
+		}
+	}
+}
+
+/// 把罚没资金存入奖励池的 `OnSlash`,使罚没的保证金用于支付之后的预言机奖励
+pub struct SlashToRewardPot<T>(sp_std::marker::PhantomData<T>);
 
+impl<T: Config> OnUnbalanced<NegativeImbalanceOf<T>> for SlashToRewardPot<T> {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+		T::Currency::resolve_creating(&Pallet::<T>::reward_pot(), amount);
 	}
 }
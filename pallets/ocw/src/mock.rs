@@ -0,0 +1,212 @@
+use crate as pallet_ocw;
+use crate::render::KittyDna;
+use frame_support::{parameter_types, traits::GenesisBuild, PalletId};
+use frame_system as system;
+use sp_core::{sr25519::Signature, H256};
+use std::cell::RefCell;
+use sp_runtime::{
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+	Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+pub type Extrinsic = TestXt<Call, ()>;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		OcwModule: pallet_ocw::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u128>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 1;
+	pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = MaxLocks;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u128;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+parameter_types! {
+	pub const MaxNumbers: u32 = 3;
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const MaxFeeds: u32 = 2;
+	pub const MaxUrlLength: u32 = 64;
+	pub const MaxOracles: u32 = 3;
+	pub const GracePeriod: u64 = 2;
+	pub const OracleBond: u128 = 1_000;
+	pub const RewardPerRound: u128 = 10;
+	pub const Tolerance: Permill = Permill::from_percent(10);
+	pub const MedianHistory: u32 = 2;
+	pub const OutlierSlash: u128 = 100;
+	pub const MissedRoundSlash: u128 = 50;
+	pub const MaxRenders: u32 = 2;
+	pub const OcwPalletId: PalletId = PalletId(*b"py/oracl");
+	pub const ChallengerReward: Permill = Permill::from_percent(50);
+}
+
+impl pallet_ocw::Config for Test {
+	type AuthorityId = pallet_ocw::crypto::OcwAuthId;
+	type Event = Event;
+	type Call = Call;
+	type MaxNumbers = MaxNumbers;
+	type UnsignedPriority = UnsignedPriority;
+	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxFeeds = MaxFeeds;
+	type MaxUrlLength = MaxUrlLength;
+	type MaxOracles = MaxOracles;
+	type GracePeriod = GracePeriod;
+	type Currency = Balances;
+	type OracleBond = OracleBond;
+	type RewardPerRound = RewardPerRound;
+	type Tolerance = Tolerance;
+	type MedianHistory = MedianHistory;
+	type OutlierSlash = OutlierSlash;
+	type MissedRoundSlash = MissedRoundSlash;
+	type OnSlash = ();
+	type PalletId = OcwPalletId;
+	type ChallengerReward = ChallengerReward;
+	type KittyIndex = u32;
+	type Kitties = MockKitties;
+	type MaxRenders = MaxRenders;
+}
+
+thread_local! {
+	static KITTY_DNA: RefCell<Vec<[u8; 16]>> = RefCell::new(Vec::new());
+}
+
+/// 测试用的kitty来源,索引从1开始
+pub struct MockKitties;
+
+impl KittyDna<u32> for MockKitties {
+	fn last_kitty_id() -> Option<u32> {
+		KITTY_DNA.with(|kitties| match kitties.borrow().len() {
+			0 => None,
+			len => Some(len as u32),
+		})
+	}
+
+	fn dna(kitty_id: u32) -> Option<[u8; 16]> {
+		KITTY_DNA.with(|kitties| kitties.borrow().get((kitty_id as usize).checked_sub(1)?).copied())
+	}
+}
+
+/// 创建一个kitty,返回索引
+pub fn create_kitty(dna: [u8; 16]) -> u32 {
+	KITTY_DNA.with(|kitties| {
+		kitties.borrow_mut().push(dna);
+		kitties.borrow().len() as u32
+	})
+}
+
+/// 创世时注册的数据源,每5个区块一轮,价格保留两位小数
+pub const FEED_ID: u32 = 1;
+pub const FEED_URL: &[u8] = b"https://price-a.example/usd";
+
+/// 预言机白名单中的账户
+pub fn oracle(i: u8) -> AccountId {
+	sp_core::sr25519::Public::from_raw([i; 32])
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	// 创世时预言机会锁定保证金,oracle(4)余额不足以锁定保证金
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![
+			(oracle(1), 10_000),
+			(oracle(2), 10_000),
+			(oracle(3), 10_000),
+			(oracle(4), 500),
+			(OcwModule::reward_pot(), 1_000),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_ocw::GenesisConfig::<Test> {
+		oracles: vec![oracle(1), oracle(2), oracle(3)],
+		feeds: vec![(FEED_ID, FEED_URL.to_vec(), b"USD".to_vec(), 2, 5)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	KITTY_DNA.with(|kitties| kitties.borrow_mut().clear());
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
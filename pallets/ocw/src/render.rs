@@ -0,0 +1,144 @@
+//! 根据kitty的dna生成确定性的SVG图像
+//!
+//! 渲染只依赖dna,任何人都可以用同样的代码重新生成图像并与链上记录的哈希比对。
+//! 链下工作机渲染新创建的kitty并提交图像哈希,链上在被质疑时重新渲染校验。
+
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+/// 提供kitty的dna,由runtime桥接到kitty模块
+pub trait KittyDna<KittyIndex> {
+	/// 最新创建的kitty的索引,没有kitty时为空
+	fn last_kitty_id() -> Option<KittyIndex>;
+	/// kitty的dna,kitty不存在时为空
+	fn dna(kitty_id: KittyIndex) -> Option<[u8; 16]>;
+}
+
+/// 渲染kitty图像
+///
+/// dna各字节的含义:
+/// - 0..3: 身体颜色
+/// - 3..6: 花纹颜色
+/// - 6..9: 眼睛颜色
+/// - 9: 花纹样式(无、条纹、斑点、色块)
+/// - 10: 耳朵样式(尖耳、圆耳、折耳)
+/// - 11: 眼睛大小
+/// - 12: 嘴巴样式(微笑、平嘴、张嘴)
+/// - 13..16: 背景颜色
+pub fn render_svg(dna: &[u8; 16]) -> Vec<u8> {
+	let mut svg = Svg(Vec::with_capacity(1024));
+	svg.text("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 200 200\">");
+	svg.text("<rect width=\"200\" height=\"200\" fill=\"");
+	svg.color(&dna[13..16]);
+	svg.text("\"/>");
+
+	// 耳朵
+	match dna[10] % 3 {
+		0 => {
+			svg.path("M50 80L60 25L95 60Z", &dna[0..3]);
+			svg.path("M150 80L140 25L105 60Z", &dna[0..3]);
+		},
+		1 => {
+			svg.circle(60, 50, 22, &dna[0..3]);
+			svg.circle(140, 50, 22, &dna[0..3]);
+		},
+		_ => {
+			svg.path("M45 75Q55 35 95 55Z", &dna[0..3]);
+			svg.path("M155 75Q145 35 105 55Z", &dna[0..3]);
+		},
+	}
+
+	// 脸
+	svg.circle(100, 110, 65, &dna[0..3]);
+
+	// 花纹
+	match dna[9] % 4 {
+		1 =>
+			for y in [70u32, 85, 100].iter() {
+				svg.text("<rect x=\"70\" y=\"");
+				svg.num(*y);
+				svg.text("\" width=\"60\" height=\"6\" fill=\"");
+				svg.color(&dna[3..6]);
+				svg.text("\"/>");
+			},
+		2 =>
+			for (x, y) in [(60u32, 120u32), (140, 120), (100, 75)].iter() {
+				svg.circle(*x, *y, 8, &dna[3..6]);
+			},
+		3 => svg.path("M100 45A65 65 0 0 1 165 110L100 110Z", &dna[3..6]),
+		_ => {},
+	}
+
+	// 眼睛
+	let eye = 6 + u32::from(dna[11] % 8);
+	svg.circle(75, 105, eye, &dna[6..9]);
+	svg.circle(125, 105, eye, &dna[6..9]);
+
+	// 嘴巴
+	svg.text("<path d=\"");
+	svg.text(match dna[12] % 3 {
+		0 => "M85 140Q100 155 115 140",
+		1 => "M85 145L115 145",
+		_ => "M90 140Q100 160 110 140Z",
+	});
+	svg.text("\" stroke=\"#000\" stroke-width=\"3\" fill=\"none\"/>");
+
+	svg.text("</svg>");
+	svg.0
+}
+
+/// kitty图像的哈希,即SVG字节的blake2_256
+pub fn render_hash(dna: &[u8; 16]) -> H256 {
+	H256(sp_io::hashing::blake2_256(&render_svg(dna)))
+}
+
+/// 不依赖 `format!` 的SVG拼接
+struct Svg(Vec<u8>);
+
+impl Svg {
+	fn text(&mut self, text: &str) {
+		self.0.extend_from_slice(text.as_bytes());
+	}
+
+	fn num(&mut self, mut n: u32) {
+		let start = self.0.len();
+		loop {
+			self.0.push(b'0' + (n % 10) as u8);
+			n /= 10;
+			if n == 0 {
+				break
+			}
+		}
+		self.0[start..].reverse();
+	}
+
+	/// `#rrggbb` 格式的颜色
+	fn color(&mut self, rgb: &[u8]) {
+		const HEX: &[u8; 16] = b"0123456789abcdef";
+		self.0.push(b'#');
+		for byte in rgb {
+			self.0.push(HEX[(byte >> 4) as usize]);
+			self.0.push(HEX[(byte & 0xf) as usize]);
+		}
+	}
+
+	fn circle(&mut self, cx: u32, cy: u32, r: u32, rgb: &[u8]) {
+		self.text("<circle cx=\"");
+		self.num(cx);
+		self.text("\" cy=\"");
+		self.num(cy);
+		self.text("\" r=\"");
+		self.num(r);
+		self.text("\" fill=\"");
+		self.color(rgb);
+		self.text("\"/>");
+	}
+
+	fn path(&mut self, d: &str, rgb: &[u8]) {
+		self.text("<path d=\"");
+		self.text(d);
+		self.text("\" fill=\"");
+		self.color(rgb);
+		self.text("\"/>");
+	}
+}
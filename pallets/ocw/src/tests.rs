@@ -0,0 +1,771 @@
+use super::*;
+use crate::mock::{
+	create_kitty, new_test_ext, oracle, Balances, Call as MockCall, Event as MockEvent, Extrinsic,
+	OcwModule, Origin, System, Test, FEED_ID,
+};
+use crate::render::{render_hash, render_svg};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, Hooks, ReservableCurrency},
+	unsigned::ValidateUnsigned,
+};
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt},
+	sr25519::{Public, Signature},
+	H256,
+};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{
+	offchain::{
+		storage::StorageValueRef,
+		storage_lock::{BlockAndTime, StorageLock},
+		Duration,
+	},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	RuntimeAppPublic,
+};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+const PHRASE: &str =
+	"news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+
+/// 注册链下工作机、交易池及keystore扩展,返回测试环境、链下状态、交易池状态和链下工作机公钥
+fn offchain_ext() -> (
+	sp_io::TestExternalities,
+	Arc<RwLock<testing::OffchainState>>,
+	Arc<RwLock<testing::PoolState>>,
+	Public,
+) {
+	let keystore = KeyStore::new();
+	let public = SyncCryptoStore::sr25519_generate_new(
+		&keystore,
+		crate::crypto::Public::ID,
+		Some(&format!("{}/hunter1", PHRASE)),
+	)
+	.unwrap();
+
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+
+	let mut t = new_test_ext();
+	t.register_extension(OffchainDbExt::new(offchain.clone()));
+	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(Arc::new(keystore)));
+	(t, state, pool_state, public)
+}
+
+#[test]
+fn test_submit_number_signed() {
+	new_test_ext().execute_with(|| {
+		let who = Public::from_raw([1; 32]);
+		assert_ok!(OcwModule::submit_number_signed(Origin::signed(who), 7));
+		assert_eq!(OcwModule::numbers().into_inner(), vec![7]);
+	});
+}
+
+#[test]
+fn test_numbers_keep_latest() {
+	new_test_ext().execute_with(|| {
+		let who = Public::from_raw([1; 32]);
+		for number in 1..=4 {
+			assert_ok!(OcwModule::submit_number_signed(Origin::signed(who), number));
+		}
+		assert_eq!(OcwModule::numbers().into_inner(), vec![2, 3, 4]);
+	});
+}
+
+#[test]
+fn test_submit_unsigned_requires_none_origin() {
+	new_test_ext().execute_with(|| {
+		let who = Public::from_raw([1; 32]);
+		let payload = Payload { number: 1, public: who };
+		let signature = Signature::from_raw([0; 64]);
+		assert_noop!(
+			OcwModule::submit_number_unsigned_with_signed_payload(Origin::signed(who), payload, signature),
+			sp_runtime::traits::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn test_offchain_signed_tx() {
+	let (mut t, _state, pool_state, _public) = offchain_ext();
+	t.execute_with(|| {
+		assert_ok!(OcwModule::offchain_signed_tx(42));
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature.unwrap().0, 0);
+		assert_eq!(tx.call, MockCall::OcwModule(crate::Call::submit_number_signed { number: 42 }));
+	});
+}
+
+#[test]
+fn test_offchain_unsigned_tx_signed_payload() {
+	let (mut t, _state, pool_state, public) = offchain_ext();
+	t.execute_with(|| {
+		assert_ok!(OcwModule::offchain_unsigned_tx_signed_payload(43));
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert!(tx.signature.is_none());
+		if let MockCall::OcwModule(crate::Call::submit_number_unsigned_with_signed_payload {
+			payload,
+			signature,
+		}) = tx.call
+		{
+			assert_eq!(payload, Payload { number: 43, public });
+			let call = crate::Call::submit_number_unsigned_with_signed_payload {
+				payload: payload.clone(),
+				signature: signature.clone(),
+			};
+			// 不在预言机白名单中的密钥被拒绝
+			assert_eq!(
+				OcwModule::validate_unsigned(TransactionSource::Local, &call),
+				InvalidTransaction::BadSigner.into()
+			);
+			Oracles::<Test>::mutate(|oracles| oracles.try_push(public).unwrap());
+			let valid = OcwModule::validate_unsigned(TransactionSource::Local, &call).unwrap();
+			assert_eq!(valid.provides, vec![("pallet-ocw", (1u64, public)).encode()]);
+
+			// 篡改数据后签名校验失败
+			let forged = crate::Call::submit_number_unsigned_with_signed_payload {
+				payload: Payload { number: 44, public },
+				signature,
+			};
+			assert_eq!(
+				OcwModule::validate_unsigned(TransactionSource::Local, &forged),
+				InvalidTransaction::BadProof.into()
+			);
+		} else {
+			panic!("unexpected call: {:?}", tx.call);
+		}
+	});
+}
+
+#[test]
+fn test_offchain_without_key() {
+	let mut t = new_test_ext();
+	let (pool, _pool_state) = testing::TestTransactionPoolExt::new();
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+	t.execute_with(|| {
+		assert!(matches!(OcwModule::offchain_signed_tx(1), Err(Error::<Test>::NoLocalAcctForSigning)));
+	});
+}
+
+/// 模拟价格数据源的HTTP响应
+fn expect_price_request(state: &Arc<RwLock<testing::OffchainState>>, uri: &str, body: &[u8]) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: uri.into(),
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+#[test]
+fn test_parse_price() {
+	assert_eq!(OcwModule::parse_price(r#"{"USD": 12.34}"#, "USD", 2), Some(1234));
+	assert_eq!(OcwModule::parse_price(r#"{"USD": 12.3}"#, "USD", 2), Some(1230));
+	assert_eq!(OcwModule::parse_price(r#"{"USD": 12.3456}"#, "USD", 2), Some(1234));
+	assert_eq!(OcwModule::parse_price(r#"{"USD": 7}"#, "USD", 0), Some(7));
+	assert_eq!(OcwModule::parse_price(r#"{"USD": 7}"#, "USD", 8), Some(700_000_000));
+	assert_eq!(OcwModule::parse_price(r#"{"EUR": 7}"#, "USD", 2), None);
+	assert_eq!(OcwModule::parse_price("not json", "USD", 2), None);
+}
+
+#[test]
+fn test_parse_price_with_path() {
+	let body = r#"{"data": {"base": "DOT", "amount": "27.155"}}"#;
+	assert_eq!(OcwModule::parse_price(body, "data.amount", 2), Some(2715));
+	assert_eq!(OcwModule::parse_price(body, "data.base", 2), None);
+	assert_eq!(OcwModule::parse_price(body, "data.missing", 2), None);
+
+	let body = r#"{"prices": [[1, 2.5], [2, 3.5]]}"#;
+	assert_eq!(OcwModule::parse_price(body, "prices.1.1", 1), Some(35));
+	assert_eq!(OcwModule::parse_price(body, "prices.5.1", 1), None);
+	assert_eq!(OcwModule::parse_price(r#"{"USD": "1.2.3"}"#, "USD", 2), None);
+	assert_eq!(OcwModule::parse_price(r#"{"USD": "-1"}"#, "USD", 2), None);
+}
+
+#[test]
+fn test_fetch_price() {
+	let (mut t, state, _pool_state, _public) = offchain_ext();
+	expect_price_request(&state, "https://price-a.example/usd", br#"{"USD": 155.23}"#);
+	t.execute_with(|| {
+		assert_eq!(OcwModule::fetch_price("https://price-a.example/usd", "USD", 2).unwrap(), 15523);
+	});
+}
+
+#[test]
+fn test_fetch_price_invalid_body() {
+	let (mut t, state, _pool_state, _public) = offchain_ext();
+	expect_price_request(&state, "https://price-a.example/usd", b"<html></html>");
+	t.execute_with(|| {
+		assert!(matches!(
+			OcwModule::fetch_price("https://price-a.example/usd", "USD", 2),
+			Err(Error::<Test>::ConvertError)
+		));
+	});
+}
+
+#[test]
+fn test_fetch_and_submit_price() {
+	let (mut t, state, pool_state, _public) = offchain_ext();
+	expect_price_request(&state, "https://price-a.example/usd", br#"{"USD": 11.00}"#);
+	t.execute_with(|| {
+		System::set_block_number(5);
+		OcwModule::offchain_worker(5);
+
+		// 数字和价格各提交一笔交易
+		assert!(take_calls(&pool_state).contains(&MockCall::OcwModule(crate::Call::submit_price {
+			feed_id: FEED_ID,
+			round: 1,
+			price: 1100
+		})));
+	});
+}
+
+#[test]
+fn test_offchain_worker_reads_registry() {
+	let (mut t, state, pool_state, _public) = offchain_ext();
+	// 已移除的数据源不再请求(未预期的请求会导致测试失败)
+	expect_price_request(&state, "https://price-b.example/btc", br#"{"data": {"amount": "50000.5"}}"#);
+	t.execute_with(|| {
+		assert_ok!(OcwModule::remove_feed(Origin::root(), FEED_ID));
+		assert_ok!(OcwModule::register_feed(
+			Origin::root(),
+			2,
+			b"https://price-b.example/btc".to_vec(),
+			b"data.amount".to_vec(),
+			1,
+			10
+		));
+		System::set_block_number(10);
+		OcwModule::offchain_worker(10);
+
+		let calls = take_calls(&pool_state);
+		assert!(calls.contains(&MockCall::OcwModule(crate::Call::submit_price {
+			feed_id: 2,
+			round: 1,
+			price: 500_005
+		})));
+	});
+}
+
+#[test]
+fn test_submit_price() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 100));
+		assert_eq!(OcwModule::round_prices(FEED_ID, 0).into_inner(), vec![(oracle(1), 100)]);
+		System::assert_last_event(MockEvent::OcwModule(Event::PriceSubmitted(oracle(1), FEED_ID, 0, 100)));
+
+		assert_noop!(
+			OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 101),
+			Error::<Test>::AlreadySubmitted
+		);
+		assert_noop!(
+			OcwModule::submit_price(Origin::signed(oracle(2)), FEED_ID, 1, 101),
+			Error::<Test>::WrongRound
+		);
+		assert_noop!(
+			OcwModule::submit_price(Origin::signed(oracle(9)), FEED_ID, 0, 101),
+			Error::<Test>::NotOracle
+		);
+		assert_noop!(
+			OcwModule::submit_price(Origin::signed(oracle(2)), 9, 0, 101),
+			Error::<Test>::FeedNotFound
+		);
+	});
+}
+
+#[test]
+fn test_price_aggregated_per_round() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 300));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(2)), FEED_ID, 0, 100));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(3)), FEED_ID, 0, 200));
+
+		System::set_block_number(5);
+		OcwModule::on_initialize(5);
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), Some(200));
+		assert_eq!(OcwModule::price(FEED_ID), Some((0, 200)));
+		assert!(OcwModule::round_prices(FEED_ID, 0).is_empty());
+		System::assert_has_event(MockEvent::OcwModule(Event::PriceAggregated(FEED_ID, 0, 200, 3)));
+	});
+}
+
+/// 所有预言机以相同价格报价,并在下一轮开始时结算该轮
+fn run_round(round: u64, price: u64) {
+	System::set_block_number(round * 5);
+	for i in 1..=3 {
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(i)), FEED_ID, round, price));
+	}
+	System::set_block_number((round + 1) * 5);
+	OcwModule::on_initialize((round + 1) * 5);
+}
+
+#[test]
+fn test_medians_keep_latest_rounds() {
+	new_test_ext().execute_with(|| {
+		run_round(0, 100);
+		run_round(1, 101);
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), Some(100));
+		run_round(2, 102);
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), None);
+		assert_eq!(OcwModule::median_of(FEED_ID, 1), Some(101));
+		assert_eq!(OcwModule::median_of(FEED_ID, 2), Some(102));
+		assert_eq!(Medians::<Test>::iter_prefix(FEED_ID).count(), 2);
+	});
+}
+
+#[test]
+fn test_remove_feed_clears_rounds() {
+	new_test_ext().execute_with(|| {
+		run_round(0, 100);
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 1, 101));
+		assert_ok!(OcwModule::remove_feed(Origin::root(), FEED_ID));
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), None);
+		assert!(OcwModule::round_prices(FEED_ID, 1).is_empty());
+		assert_eq!(OcwModule::price(FEED_ID), None);
+	});
+}
+
+#[test]
+fn test_interval_change_clears_rounds() {
+	new_test_ext().execute_with(|| {
+		let url = crate::mock::FEED_URL.to_vec();
+		run_round(0, 100);
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 1, 101));
+
+		// 每轮区块数不变时保留报价和中位数
+		assert_ok!(OcwModule::register_feed(Origin::root(), FEED_ID, url.clone(), b"EUR".to_vec(), 2, 5));
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), Some(100));
+		assert_eq!(OcwModule::round_prices(FEED_ID, 1).len(), 1);
+
+		assert_ok!(OcwModule::register_feed(Origin::root(), FEED_ID, url, b"EUR".to_vec(), 2, 10));
+		assert_eq!(OcwModule::median_of(FEED_ID, 0), None);
+		assert!(OcwModule::round_prices(FEED_ID, 1).is_empty());
+		// 新的轮次编号下可以重新报价
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 101));
+	});
+}
+
+#[test]
+fn test_round_rewards_and_slashes() {
+	new_test_ext().execute_with(|| {
+		// 中位数200,容忍偏差10%
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 210));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(2)), FEED_ID, 0, 200));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(3)), FEED_ID, 0, 300));
+
+		System::set_block_number(5);
+		OcwModule::on_initialize(5);
+		System::assert_has_event(MockEvent::OcwModule(Event::OracleRewarded(oracle(1), FEED_ID, 0, 10)));
+		System::assert_has_event(MockEvent::OcwModule(Event::OracleRewarded(oracle(2), FEED_ID, 0, 10)));
+		System::assert_has_event(MockEvent::OcwModule(Event::OracleSlashed(
+			oracle(3),
+			FEED_ID,
+			0,
+			100,
+			SlashReason::Outlier,
+		)));
+
+		assert_eq!(Balances::free_balance(oracle(1)), 9_010);
+		assert_eq!(Balances::free_balance(oracle(3)), 9_000);
+		assert_eq!(Balances::reserved_balance(oracle(3)), 900);
+		assert_eq!(OcwModule::bonds(oracle(3)), 900);
+		// 奖励从奖励池支付
+		assert_eq!(Balances::free_balance(OcwModule::reward_pot()), 980);
+	});
+}
+
+#[test]
+fn test_round_reward_skipped_when_pot_is_empty() {
+	new_test_ext().execute_with(|| {
+		let _ = Balances::slash(&OcwModule::reward_pot(), 1_000);
+		let issuance = Balances::total_issuance();
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 200));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(2)), FEED_ID, 0, 200));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(3)), FEED_ID, 0, 200));
+
+		System::set_block_number(5);
+		OcwModule::on_initialize(5);
+		System::assert_has_event(MockEvent::OcwModule(Event::RewardSkipped(oracle(1), FEED_ID, 0, 10)));
+		assert_eq!(Balances::free_balance(oracle(1)), 9_000);
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
+
+#[test]
+fn test_missed_round_slashed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), FEED_ID, 0, 200));
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(2)), FEED_ID, 0, 200));
+
+		System::set_block_number(5);
+		OcwModule::on_initialize(5);
+		System::assert_has_event(MockEvent::OcwModule(Event::OracleSlashed(
+			oracle(3),
+			FEED_ID,
+			0,
+			50,
+			SlashReason::Missed,
+		)));
+		assert_eq!(OcwModule::bonds(oracle(3)), 950);
+
+		// 保证金不足时不能报价,补足后可以继续报价
+		assert_noop!(
+			OcwModule::submit_price(Origin::signed(oracle(3)), FEED_ID, 1, 200),
+			Error::<Test>::NotBonded
+		);
+		assert_ok!(OcwModule::bond(Origin::signed(oracle(3))));
+		System::assert_last_event(MockEvent::OcwModule(Event::Bonded(oracle(3), 50)));
+		assert_eq!(Balances::reserved_balance(oracle(3)), 1_000);
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(3)), FEED_ID, 1, 200));
+	});
+}
+
+#[test]
+fn test_empty_round_not_slashed() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(5);
+		OcwModule::on_initialize(5);
+		assert_eq!(OcwModule::price(FEED_ID), None);
+		for i in 1..=3 {
+			assert_eq!(OcwModule::bonds(oracle(i)), 1_000);
+		}
+	});
+}
+
+#[test]
+fn test_bond_and_unbond() {
+	new_test_ext().execute_with(|| {
+		// 创世预言机已锁定保证金
+		assert_eq!(Balances::reserved_balance(oracle(1)), 1_000);
+		assert_ok!(OcwModule::bond(Origin::signed(oracle(1))));
+		assert_eq!(Balances::reserved_balance(oracle(1)), 1_000);
+
+		assert_noop!(OcwModule::bond(Origin::signed(oracle(4))), Error::<Test>::InsufficientBalance);
+		assert_noop!(OcwModule::unbond(Origin::signed(oracle(4))), Error::<Test>::NotBonded);
+		assert_noop!(OcwModule::unbond(Origin::signed(oracle(1))), Error::<Test>::StillOracle);
+
+		assert_ok!(OcwModule::remove_oracle(Origin::root(), oracle(1)));
+		assert_ok!(OcwModule::unbond(Origin::signed(oracle(1))));
+		System::assert_last_event(MockEvent::OcwModule(Event::Unbonded(oracle(1), 1_000)));
+		assert_eq!(Balances::reserved_balance(oracle(1)), 0);
+		assert_eq!(OcwModule::bonds(oracle(1)), 0);
+	});
+}
+
+#[test]
+fn test_slash_limited_to_bond() {
+	new_test_ext().execute_with(|| {
+		// 其他模块锁定的资金不会被罚没
+		assert_ok!(Balances::reserve(&oracle(3), 500));
+		Bonds::<Test>::insert(oracle(3), 30);
+		assert_eq!(OcwModule::slash(&oracle(3), 100), 30);
+		assert_eq!(OcwModule::bonds(oracle(3)), 0);
+		assert_eq!(Balances::reserved_balance(oracle(3)), 1_470);
+	});
+}
+
+#[test]
+fn test_median() {
+	assert_eq!(OcwModule::median(&mut []), None);
+	assert_eq!(OcwModule::median(&mut [3, 1, 2]), Some(2));
+	assert_eq!(OcwModule::median(&mut [4, 1, 2, 3]), Some(2));
+	assert_eq!(OcwModule::median(&mut [u64::MAX, u64::MAX]), Some(u64::MAX));
+}
+
+#[test]
+fn test_set_oracles() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			OcwModule::set_oracles(Origin::signed(oracle(1)), vec![oracle(4)]),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_noop!(
+			OcwModule::set_oracles(Origin::root(), (1..=4).map(oracle).collect()),
+			Error::<Test>::TooManyOracles
+		);
+		assert_ok!(OcwModule::set_oracles(Origin::root(), vec![oracle(4)]));
+		assert_eq!(OcwModule::oracles().into_inner(), vec![oracle(4)]);
+	});
+}
+
+#[test]
+fn test_add_and_remove_oracle() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(OcwModule::add_oracle(Origin::root(), oracle(1)), Error::<Test>::AlreadyOracle);
+		assert_noop!(OcwModule::add_oracle(Origin::root(), oracle(4)), Error::<Test>::TooManyOracles);
+		assert_ok!(OcwModule::remove_oracle(Origin::root(), oracle(1)));
+		assert_noop!(OcwModule::remove_oracle(Origin::root(), oracle(1)), Error::<Test>::NotOracle);
+		assert_ok!(OcwModule::add_oracle(Origin::root(), oracle(4)));
+		assert_eq!(OcwModule::oracles().into_inner(), vec![oracle(2), oracle(3), oracle(4)]);
+		System::assert_last_event(MockEvent::OcwModule(Event::OracleAdded(oracle(4))));
+	});
+}
+
+#[test]
+fn test_register_and_remove_feed() {
+	new_test_ext().execute_with(|| {
+		let url = b"https://price-b.example/btc".to_vec();
+		assert_noop!(
+			OcwModule::register_feed(Origin::signed(oracle(1)), 2, url.clone(), b"USD".to_vec(), 2, 5),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_noop!(
+			OcwModule::register_feed(Origin::root(), 2, url.clone(), b"USD".to_vec(), 2, 0),
+			Error::<Test>::InvalidInterval
+		);
+		assert_noop!(
+			OcwModule::register_feed(Origin::root(), 2, vec![b'a'; 65], b"USD".to_vec(), 2, 5),
+			Error::<Test>::UrlTooLong
+		);
+
+		assert_ok!(OcwModule::register_feed(Origin::root(), 2, url.clone(), b"USD".to_vec(), 2, 5));
+		assert_eq!(OcwModule::feed_count(), 2);
+		assert_noop!(
+			OcwModule::register_feed(Origin::root(), 3, url.clone(), b"USD".to_vec(), 2, 5),
+			Error::<Test>::TooManyFeeds
+		);
+
+		// 更新已有的数据源不占用数量
+		assert_ok!(OcwModule::register_feed(Origin::root(), 2, url, b"EUR".to_vec(), 4, 10));
+		assert_eq!(OcwModule::feed_count(), 2);
+		assert_eq!(OcwModule::feeds(2).unwrap().decimals, 4);
+
+		assert_ok!(OcwModule::submit_price(Origin::signed(oracle(1)), 2, 0, 1));
+		assert_ok!(OcwModule::remove_feed(Origin::root(), 2));
+		assert_eq!(OcwModule::feed_count(), 1);
+		assert!(OcwModule::round_prices(2, 0).is_empty());
+		assert_noop!(OcwModule::remove_feed(Origin::root(), 2), Error::<Test>::FeedNotFound);
+		System::assert_last_event(MockEvent::OcwModule(Event::FeedRemoved(2)));
+	});
+}
+
+/// 取出交易池中的所有交易
+fn take_calls(pool_state: &Arc<RwLock<testing::PoolState>>) -> Vec<MockCall> {
+	std::mem::take(&mut pool_state.write().transactions)
+		.iter()
+		.map(|tx| Extrinsic::decode(&mut &**tx).unwrap().call)
+		.collect()
+}
+
+fn job_state(key: &[u8]) -> JobState {
+	StorageValueRef::persistent(key).get::<JobState>().unwrap().unwrap_or_default()
+}
+
+#[test]
+fn test_offchain_worker_skips_when_locked() {
+	let (mut t, _state, pool_state, _public) = offchain_ext();
+	t.execute_with(|| {
+		System::set_block_number(2);
+		let mut lock = StorageLock::<BlockAndTime<frame_system::Pallet<Test>>>::with_block_and_time_deadline(
+			b"pallet-ocw::lock",
+			3,
+			Duration::from_millis(10_000),
+		);
+		let guard = lock.try_lock().unwrap();
+		OcwModule::offchain_worker(2);
+		assert!(take_calls(&pool_state).is_empty());
+
+		drop(guard);
+		OcwModule::offchain_worker(2);
+		assert_eq!(take_calls(&pool_state).len(), 1);
+	});
+}
+
+#[test]
+fn test_offchain_worker_grace_period() {
+	let (mut t, _state, pool_state, _public) = offchain_ext();
+	t.execute_with(|| {
+		System::set_block_number(2);
+		OcwModule::offchain_worker(2);
+		assert_eq!(take_calls(&pool_state).len(), 1);
+		assert_eq!(job_state(NUMBER_JOB).last_submitted, Some(2));
+
+		// 宽限期内不再提交
+		System::set_block_number(3);
+		OcwModule::offchain_worker(3);
+		assert!(take_calls(&pool_state).is_empty());
+
+		System::set_block_number(4);
+		OcwModule::offchain_worker(4);
+		assert_eq!(
+			take_calls(&pool_state),
+			vec![MockCall::OcwModule(crate::Call::submit_number_signed { number: 4 })]
+		);
+	});
+}
+
+#[test]
+fn test_price_job_backoff() {
+	let (mut t, state, pool_state, _public) = offchain_ext();
+	let job = OcwModule::price_job_key(FEED_ID);
+	expect_price_request(&state, "https://price-a.example/usd", b"oops");
+	t.execute_with(|| {
+		System::set_block_number(5);
+		OcwModule::offchain_worker(5);
+		let price_job = job_state(&job);
+		assert_eq!(price_job.failures, 1);
+		assert_eq!(price_job.retry_at, 7);
+		assert_eq!(price_job.last_submitted, None);
+		take_calls(&pool_state);
+
+		// 退避期内不会发起HTTP请求(未预期的请求会导致测试失败)
+		System::set_block_number(6);
+		OcwModule::offchain_worker(6);
+		assert_eq!(job_state(&job).last_run, 5);
+	});
+
+	expect_price_request(&state, "https://price-a.example/usd", br#"{"USD": 2.00}"#);
+	t.execute_with(|| {
+		System::set_block_number(7);
+		OcwModule::offchain_worker(7);
+		assert!(take_calls(&pool_state).contains(&MockCall::OcwModule(crate::Call::submit_price {
+			feed_id: FEED_ID,
+			round: 1,
+			price: 200
+		})));
+		assert_eq!(job_state(&job), JobState { last_run: 7, last_submitted: Some(7), failures: 0, retry_at: 0 });
+
+		// 本轮已经提交过,不再获取价格
+		System::set_block_number(9);
+		OcwModule::offchain_worker(9);
+		assert_eq!(job_state(&job).last_run, 7);
+	});
+}
+
+#[test]
+fn test_backoff() {
+	assert_eq!(OcwModule::backoff(1), 2);
+	assert_eq!(OcwModule::backoff(3), 8);
+	assert_eq!(OcwModule::backoff(10), 64);
+	assert_eq!(OcwModule::backoff(100), 64);
+}
+
+#[test]
+fn test_render_svg() {
+	let dna = [7u8; 16];
+	let svg = render_svg(&dna);
+	assert!(svg.starts_with(b"<svg "));
+	assert!(svg.ends_with(b"</svg>"));
+	// 背景颜色取自dna的最后三个字节
+	assert!(svg.windows(7).any(|w| w == b"#070707"));
+	assert_eq!(render_svg(&dna), svg);
+	assert_eq!(render_hash(&dna), H256(sp_io::hashing::blake2_256(&svg)));
+
+	let mut other = dna;
+	other[12] = 8;
+	assert_ne!(render_hash(&other), render_hash(&dna));
+}
+
+#[test]
+fn test_submit_render_hashes() {
+	new_test_ext().execute_with(|| {
+		let first = create_kitty([1; 16]);
+		let second = create_kitty([2; 16]);
+		let third = create_kitty([3; 16]);
+
+		assert_noop!(
+			OcwModule::submit_render_hashes(Origin::signed(oracle(9)), vec![(first, H256::zero())]),
+			Error::<Test>::NotOracle
+		);
+		assert_noop!(
+			OcwModule::submit_render_hashes(Origin::signed(oracle(1)), vec![(9, H256::zero())]),
+			Error::<Test>::KittyNotFound
+		);
+		assert_noop!(
+			OcwModule::submit_render_hashes(
+				Origin::signed(oracle(1)),
+				vec![(first, H256::zero()), (second, H256::zero()), (third, H256::zero())]
+			),
+			Error::<Test>::TooManyRenders
+		);
+
+		// 不连续提交时游标停在第一个缺口之前
+		let hash = render_hash(&[2; 16]);
+		assert_ok!(OcwModule::submit_render_hashes(Origin::signed(oracle(1)), vec![(second, hash)]));
+		System::assert_last_event(MockEvent::OcwModule(Event::RenderSubmitted(oracle(1), second, hash)));
+		assert_eq!(OcwModule::render_of(second), Some((hash, oracle(1))));
+		assert_eq!(OcwModule::render_cursor(), 0);
+
+		assert_ok!(OcwModule::submit_render_hashes(
+			Origin::signed(oracle(2)),
+			vec![(first, render_hash(&[1; 16]))]
+		));
+		assert_eq!(OcwModule::render_cursor(), second);
+
+		// 整批交易回滚
+		assert_noop!(
+			OcwModule::submit_render_hashes(
+				Origin::signed(oracle(2)),
+				vec![(third, H256::zero()), (second, H256::zero())]
+			),
+			Error::<Test>::AlreadyRendered
+		);
+		assert_eq!(OcwModule::render_of(third), None);
+	});
+}
+
+#[test]
+fn test_challenge_render() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty([5; 16]);
+		assert_noop!(
+			OcwModule::challenge_render(Origin::signed(oracle(4)), kitty_id),
+			Error::<Test>::RenderNotFound
+		);
+
+		assert_ok!(OcwModule::submit_render_hashes(
+			Origin::signed(oracle(1)),
+			vec![(kitty_id, render_hash(&[5; 16]))]
+		));
+		assert_noop!(
+			OcwModule::challenge_render(Origin::signed(oracle(4)), kitty_id),
+			Error::<Test>::RenderIsValid
+		);
+
+		// 错误的哈希被纠正,提交者的保证金全部罚没,一半奖励给质疑人
+		let kitty_id = create_kitty([6; 16]);
+		assert_ok!(OcwModule::submit_render_hashes(Origin::signed(oracle(2)), vec![(kitty_id, H256::zero())]));
+		assert_ok!(OcwModule::challenge_render(Origin::signed(oracle(4)), kitty_id));
+		let expected = render_hash(&[6; 16]);
+		System::assert_has_event(MockEvent::OcwModule(Event::RenderCorrected(
+			kitty_id,
+			oracle(2),
+			expected,
+			1_000,
+		)));
+		System::assert_last_event(MockEvent::OcwModule(Event::ChallengerRewarded(oracle(4), kitty_id, 500)));
+		assert_eq!(OcwModule::render_of(kitty_id), Some((expected, oracle(4))));
+		assert_eq!(OcwModule::bonds(oracle(2)), 0);
+		assert_eq!(Balances::reserved_balance(oracle(2)), 0);
+		assert_eq!(Balances::free_balance(oracle(4)), 1_000);
+	});
+}
+
+#[test]
+fn test_offchain_worker_submits_renders() {
+	let (mut t, _state, pool_state, _public) = offchain_ext();
+	t.execute_with(|| {
+		// 每次最多渲染 `MaxRenders` 个
+		for i in 1..=3 {
+			create_kitty([i; 16]);
+		}
+		OcwModule::offchain_worker(1);
+
+		assert!(take_calls(&pool_state).contains(&MockCall::OcwModule(crate::Call::submit_render_hashes {
+			renders: vec![(1, render_hash(&[1; 16])), (2, render_hash(&[2; 16]))],
+		})));
+		assert_eq!(job_state(RENDER_JOB).last_submitted, Some(1));
+	});
+}
@@ -0,0 +1,197 @@
+//! 存证模块的 RPC 接口
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::{Codec, Encode};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use pallet_poe_runtime_api::{PoeApi as PoeRuntimeApi, ProofInfo};
+use sc_client_api::ProofProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// 存证信息及其是否已在最终确定的区块中
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofStatus<AccountId, BlockNumber, Moment> {
+	/// 存证的所有者、区块及时间戳
+	#[serde(flatten)]
+	pub info: ProofInfo<AccountId, BlockNumber, Moment>,
+	/// 存证是否已包含在最终确定的状态中
+	pub finalized: bool,
+}
+
+/// 可离线校验的存证证明: 区块头中的状态根可校验存储证明
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation<BlockHash, AccountId, BlockNumber, Moment> {
+	/// 生成证明的区块哈希
+	pub block_hash: BlockHash,
+	/// SCALE编码的区块头
+	pub header: Bytes,
+	/// 存证相关存储项的键
+	pub keys: Vec<Bytes>,
+	/// 存储证明的trie节点
+	pub proof: Vec<Bytes>,
+	/// 解码后的存证信息,便于展示
+	pub info: ProofInfo<AccountId, BlockNumber, Moment>,
+}
+
+/// 存证模块 RPC 方法
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber, Moment> {
+	/// 校验文档哈希是否包含在批量存证中
+	#[rpc(name = "poe_verifyInclusion")]
+	fn verify_inclusion(
+		&self,
+		root: H256,
+		leaf: H256,
+		index: u32,
+		proof: Vec<H256>,
+		at: Option<BlockHash>,
+	) -> Result<bool>;
+
+	/// 查询存证的所有者、区块、时间戳及最终确定状态
+	#[rpc(name = "poe_proofOf")]
+	fn proof_of(
+		&self,
+		proof: Bytes,
+		at: Option<BlockHash>,
+	) -> Result<Option<ProofStatus<AccountId, BlockNumber, Moment>>>;
+
+	/// 生成存证的离线校验证明,默认使用最新的最终确定区块
+	#[rpc(name = "poe_attestation")]
+	fn attestation(
+		&self,
+		proof: Bytes,
+		at: Option<BlockHash>,
+	) -> Result<Option<Attestation<BlockHash, AccountId, BlockNumber, Moment>>>;
+}
+
+/// 存证模块 RPC 实现
+pub struct Poe<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Poe<C, B> {
+	/// 根据客户端创建 RPC 实例
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// 调用 Runtime API 失败时返回的错误码
+const RUNTIME_ERROR: i64 = 1;
+/// 读取区块或存储证明失败时返回的错误码
+const CLIENT_ERROR: i64 = 2;
+
+/// 将 Runtime API 错误转换为 RPC 错误
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+/// 将客户端错误转换为 RPC 错误
+fn client_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(CLIENT_ERROR),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber, Moment>
+	PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber, Moment> for Poe<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block>,
+	C: Send + Sync + 'static,
+	C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber, Moment>,
+	AccountId: Codec + PartialEq + Send + Sync + 'static,
+	BlockNumber: Codec + PartialEq + Send + Sync + 'static,
+	Moment: Codec + PartialEq + Send + Sync + 'static,
+{
+	fn verify_inclusion(
+		&self,
+		root: H256,
+		leaf: H256,
+		index: u32,
+		proof: Vec<H256>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<bool> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.verify_inclusion(&at, root, leaf, index, proof)
+			.map_err(|e| runtime_error("Unable to verify inclusion.", e))
+	}
+
+	fn proof_of(
+		&self,
+		proof: Bytes,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<ProofStatus<AccountId, BlockNumber, Moment>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let info = match api
+			.proof_of(&at, proof.to_vec())
+			.map_err(|e| runtime_error("Unable to query proof.", e))?
+		{
+			Some(info) => info,
+			None => return Ok(None),
+		};
+
+		// 在最终确定的区块中记录相同的存证即视为已最终确定
+		let finalized = BlockId::hash(self.client.info().finalized_hash);
+		let finalized = api
+			.proof_of(&finalized, proof.to_vec())
+			.map_err(|e| runtime_error("Unable to query proof.", e))?
+			.map_or(false, |f| f == info);
+
+		Ok(Some(ProofStatus { info, finalized }))
+	}
+
+	fn attestation(
+		&self,
+		proof: Bytes,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<Attestation<<Block as BlockT>::Hash, AccountId, BlockNumber, Moment>>> {
+		let api = self.client.runtime_api();
+		let block_hash = at.unwrap_or_else(|| self.client.info().finalized_hash);
+		let at = BlockId::hash(block_hash);
+		let info = match api
+			.proof_of(&at, proof.to_vec())
+			.map_err(|e| runtime_error("Unable to query proof.", e))?
+		{
+			Some(info) => info,
+			None => return Ok(None),
+		};
+		let keys = api
+			.claim_storage_keys(&at, proof.to_vec())
+			.map_err(|e| runtime_error("Unable to compute storage keys.", e))?;
+
+		let header = self
+			.client
+			.header(at)
+			.map_err(|e| client_error("Unable to read header.", e))?
+			.ok_or_else(|| client_error("Unknown block.", block_hash))?;
+		let storage_proof = self
+			.client
+			.read_proof(&at, &mut keys.iter().map(|k| k.as_slice()))
+			.map_err(|e| client_error("Unable to generate storage proof.", e))?;
+
+		Ok(Some(Attestation {
+			block_hash,
+			header: header.encode().into(),
+			keys: keys.into_iter().map(Into::into).collect(),
+			proof: storage_proof.iter_nodes().map(Into::into).collect(),
+			info,
+		}))
+	}
+}
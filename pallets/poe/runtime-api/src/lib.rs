@@ -0,0 +1,39 @@
+//! 存证模块的 Runtime API 定义
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{RuntimeDebug, H256};
+use sp_std::vec::Vec;
+
+/// 存证的所有者、区块及时间戳
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ProofInfo<AccountId, BlockNumber, Moment> {
+	/// 存证所有者
+	pub owner: AccountId,
+	/// 存证所在区块
+	pub block_number: BlockNumber,
+	/// 存证时间戳(毫秒)
+	pub timestamp: Moment,
+}
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi<AccountId, BlockNumber, Moment> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Moment: Codec,
+	{
+		/// 校验文档哈希是否为以root为根的已存证批量存证的第index个叶子
+		fn verify_inclusion(root: H256, leaf: H256, index: u32, proof: Vec<H256>) -> bool;
+
+		/// 查询存证的所有者、区块及时间戳
+		fn proof_of(proof: Vec<u8>) -> Option<ProofInfo<AccountId, BlockNumber, Moment>>;
+
+		/// 存证相关存储项的完整键,用于生成存储证明
+		fn claim_storage_keys(proof: Vec<u8>) -> Vec<Vec<u8>>;
+	}
+}
@@ -2,6 +2,8 @@
 
 pub use pallet::*;
 
+pub mod merkle;
+
 // 配置测试相关模块
 #[cfg(test)]
 mod mock;
@@ -12,20 +14,124 @@ mod tests;
 // 定义pallet模块，并设置pub访问权限
 pub mod pallet {
 	// 导入模块
+	use codec::{Decode, Encode};
 	use frame_support::dispatch::DispatchResultWithPostInfo;
 	use frame_support::pallet_prelude::*;
+	use frame_support::sp_runtime::traits::{One, Saturating, Zero};
+	use frame_support::traits::{BalanceStatus, Currency, ReservableCurrency, Time};
+	use frame_support::transactional;
 	use frame_system::ensure_signed;
 	use frame_system::pallet_prelude::{BlockNumberFor, OriginFor};
-	use sp_std::vec::Vec; 
+	use scale_info::TypeInfo;
+	use sp_core::H256;
+	use sp_std::{convert::TryFrom, vec::Vec};
+
+	type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type MomentOf<T> = <<T as Config>::TimeProvider as Time>::Moment;
+
+	/// 存证元数据(MIME类型、描述、外部URI)
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ClaimMetadata<T: Config> {
+		pub mime_type: BoundedVec<u8, T::MaxMetadataLength>,
+		pub description: BoundedVec<u8, T::MaxMetadataLength>,
+		pub uri: BoundedVec<u8, T::MaxMetadataLength>,
+	}
+
+	/// 存证过期信息(过期区块及创建时质押的押金)
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ClaimExpiry<T: Config> {
+		pub expires_at: T::BlockNumber,
+		pub deposit: BalanceOf<T>,
+	}
+
+	/// 批量存证(Merkle根)信息
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct BatchClaim<T: Config> {
+		pub owner: T::AccountId,
+		pub block_number: T::BlockNumber,
+		pub timestamp: MomentOf<T>,
+		pub leaf_count: u32,
+	}
+
+	/// 多方存证(M-of-N)的签署人及门限
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct MultiPartyClaim<T: Config> {
+		pub signers: BoundedVec<T::AccountId, T::MaxSigners>,
+		pub threshold: u32,
+	}
+
+	/// 等待共同签署人确认的多方存证
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct PendingClaim<T: Config> {
+		pub proposer: T::AccountId,
+		pub signers: BoundedVec<T::AccountId, T::MaxSigners>,
+		pub threshold: u32,
+		pub approvals: BoundedVec<T::AccountId, T::MaxSigners>,
+		pub deadline: T::BlockNumber,
+	}
+
+	/// 吊销记录(墓碑),在保护期内阻止其他账户重新注册同一存证
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Tombstone<T: Config> {
+		pub revoker: T::AccountId,
+		pub block_number: T::BlockNumber,
+		pub reason: u8,
+	}
+
+	/// 需要多方确认的存证操作
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum ClaimAction<AccountId> {
+		// 吊销存证,附带原因代码
+		Revoke(u8),
+		// 转移存证给指定账户(转移后变为单一所有者存证)
+		Transfer(AccountId),
+	}
+
+	/// 等待共同签署人确认的存证操作
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct PendingAction<T: Config> {
+		pub action: ClaimAction<T::AccountId>,
+		pub approvals: BoundedVec<T::AccountId, T::MaxSigners>,
+		pub deadline: T::BlockNumber,
+	}
 
 	// 定义配置
-	#[pallet::config] 
+	#[pallet::config]
 	// 定义trait Config 继承 frame_system::Config trait
 	pub trait Config: frame_system::Config {
 		// 定义关联类型Event，该类继承 From<Event<Self>> 和 IsType<<Self as frame_system::Config>::Event> 这两个trait
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		// 定义关联类型AssetDepositBase，该类继承 Get<usize> trait
 		type AssetDepositBase: Get<usize>;
+		// 存证时间戳来源(pallet_timestamp)
+		type TimeProvider: Time;
+		// 用于质押存证过期押金的货币
+		type Currency: ReservableCurrency<Self::AccountId>;
+		// 元数据单个字段的最大长度
+		#[pallet::constant]
+		type MaxMetadataLength: Get<u32>;
+		// 设置过期时间的存证需要质押的押金
+		#[pallet::constant]
+		type ExpiryDeposit: Get<BalanceOf<Self>>;
+		// 清理过期存证的账户从押金中获得的奖励
+		#[pallet::constant]
+		type PruneReward: Get<BalanceOf<Self>>;
+		// 多方存证的最大签署人数
+		#[pallet::constant]
+		type MaxSigners: Get<u32>;
+		// 多方存证的吊销/转移操作需要在多少个区块内获得足够确认
+		#[pallet::constant]
+		type ActionPeriod: Get<Self::BlockNumber>;
+		// 吊销后多少个区块内禁止其他账户重新注册该存证(为0时不保留吊销记录)
+		#[pallet::constant]
+		type TombstonePeriod: Get<Self::BlockNumber>;
 	}
 
 	// 定义事件枚举
@@ -37,10 +143,30 @@ pub mod pallet {
 		ClaimCreated(T::AccountId, Vec<u8>),
 		//存证吊销成功枚举类型,使用元组记录AccountId和相应数据
 		ClaimRevoked(T::AccountId, Vec<u8>),
+		//存证附带原因吊销,记录吊销人、存证和原因代码
+		ClaimRevokedWithReason(T::AccountId, Vec<u8>, u8),
+		//存证续期成功,记录所有者、存证和新的过期区块
+		ClaimRenewed(T::AccountId, Vec<u8>, T::BlockNumber),
+		//过期存证被清理,记录清理人(on_idle清理时为None)、存证和清理奖励
+		ClaimPruned(Option<T::AccountId>, Vec<u8>, BalanceOf<T>),
+		//批量存证创建成功,记录所有者、Merkle根和叶子数量
+		BatchClaimCreated(T::AccountId, H256, u32),
+		//批量存证吊销成功,记录所有者和Merkle根
+		BatchClaimRevoked(T::AccountId, H256),
+		//存证转移成功,记录原所有者、新所有者和存证
+		ClaimTransferred(T::AccountId, T::AccountId, Vec<u8>),
+		//多方存证已提议,记录提议人和存证
+		MultiPartyClaimProposed(T::AccountId, Vec<u8>),
+		//共同签署人已确认多方存证或存证操作,记录签署人和存证
+		MultiPartyApproved(T::AccountId, Vec<u8>),
+		//未完成的多方存证或存证操作已取消
+		MultiPartyCancelled(Vec<u8>),
+		//多方存证操作已提议,记录提议人、存证和操作
+		ClaimActionProposed(T::AccountId, Vec<u8>, ClaimAction<T::AccountId>),
 	}
 
 	// 定义错误枚举
-	#[pallet::error] 
+	#[pallet::error]
 	pub enum Error<T> {
 		// 存证已经创建
 		ProofAlreadyClaimed,
@@ -50,56 +176,181 @@ pub mod pallet {
 		NotProofOwner,
 		// 存证长度超过上限
 		ClaimTooLong,
+		// 过期区块必须晚于当前区块
+		ExpiryInPast,
+		// 存证尚未过期
+		ClaimNotExpired,
+		// 余额不足以质押过期押金
+		NotEnoughBalanceForDeposit,
+		// 批量存证至少需要一个叶子
+		EmptyBatch,
+		// 多方存证的签署人数超过上限
+		TooManySigners,
+		// 门限必须在1到签署人数之间
+		InvalidThreshold,
+		// 不是该多方存证的签署人
+		NotSigner,
+		// 已经确认过
+		AlreadyApproved,
+		// 确认截止区块已过
+		DeadlinePassed,
+		// 没有等待确认的多方存证或存证操作
+		NoPendingApproval,
+		// 已有等待确认的多方存证或存证操作
+		ApprovalAlreadyPending,
+		// 多方存证的吊销/转移/续期需要通过门限确认
+		RequiresThreshold,
+		// 存证已被吊销,保护期内只有原吊销人可以重新注册
+		ProofTombstoned,
 	}
 
 	// 定义结构体
+	// 存储版本,1 起PruneCursor从升级时的区块开始,2 起吊销记录按保护期结束的区块索引
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// 定义存储
-	#[pallet::storage] 
+	#[pallet::storage]
 	#[pallet::getter(fn proofs)]
 	// 对StorageMap结构体重命名，并指定pub(super)访问权限
 	pub(super) type Proofs<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, T::BlockNumber), ValueQuery>;
 
+	// 存证时的时间戳(与Proofs中的区块对应)
+	#[pallet::storage]
+	#[pallet::getter(fn claim_timestamp)]
+	pub(super) type ClaimTimestamps<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, MomentOf<T>>;
+
+	// 已吊销存证的吊销记录
+	#[pallet::storage]
+	#[pallet::getter(fn tombstones)]
+	pub(super) type Tombstones<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, Tombstone<T>>;
+
+	// 按保护期结束区块索引的吊销记录,供on_idle清理使用
+	#[pallet::storage]
+	pub(super) type TombstonesExpiringAt<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, Vec<u8>, ()>;
+
+	// 存证元数据
+	#[pallet::storage]
+	#[pallet::getter(fn metadata)]
+	pub(super) type Metadata<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, ClaimMetadata<T>>;
+
+	// 存证过期信息
+	#[pallet::storage]
+	#[pallet::getter(fn expiry)]
+	pub(super) type Expiries<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, ClaimExpiry<T>>;
+
+	// 按过期区块索引的存证,供on_idle清理使用
+	#[pallet::storage]
+	pub(super) type ExpiringAt<T: Config> = StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, Vec<u8>, ()>;
+
+	// 批量存证,以Merkle根为键
+	#[pallet::storage]
+	#[pallet::getter(fn batch_proofs)]
+	pub(super) type BatchProofs<T: Config> = StorageMap<_, Blake2_128Concat, H256, BatchClaim<T>>;
+
+	// 已生效的多方存证签署信息
+	#[pallet::storage]
+	#[pallet::getter(fn multi_party_claims)]
+	pub(super) type MultiPartyClaims<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, MultiPartyClaim<T>>;
+
+	// 等待确认的多方存证
+	#[pallet::storage]
+	#[pallet::getter(fn pending_claims)]
+	pub(super) type PendingClaims<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, PendingClaim<T>>;
+
+	// 等待确认的多方存证操作
+	#[pallet::storage]
+	#[pallet::getter(fn pending_actions)]
+	pub(super) type PendingActions<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, PendingAction<T>>;
+
+	// on_idle下一次开始清理的过期区块
+	#[pallet::storage]
+	pub(super) type PruneCursor<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
 	//定义hooks
 	#[pallet::hooks]
 	// 为Pallet实现Hooks trait
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		// 利用区块剩余权重清理已过期的存证
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::prune_expired(now, remaining_weight)
+		}
+
+		// 已有的链升级时把PruneCursor设为当前区块,避免on_idle从区块0逐个遍历;
+		// 并为已有的吊销记录建立索引,保护期已过的直接删除
+		fn on_runtime_upgrade() -> Weight {
+			let version = Self::on_chain_storage_version();
+			if version >= STORAGE_VERSION {
+				return 0
+			}
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			if version < 1 {
+				PruneCursor::<T>::put(now);
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+			if version < 2 {
+				let tombstones: Vec<(Vec<u8>, T::BlockNumber)> =
+					Tombstones::<T>::iter().map(|(proof, t)| (proof, t.block_number)).collect();
+				for (proof, block_number) in tombstones {
+					let expires_at = block_number.saturating_add(T::TombstonePeriod::get());
+					if expires_at <= now {
+						Tombstones::<T>::remove(&proof);
+					} else {
+						TombstonesExpiringAt::<T>::insert(expires_at, &proof, ());
+					}
+					weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+				}
+			}
+			STORAGE_VERSION.put::<Self>();
+			weight
+		}
+	}
 
 	// 定义调用
-	#[pallet::call] 
+	#[pallet::call]
 	// 为Pallet结构体扩展方法
 	impl<T: Config> Pallet<T> {
 
 		// 定义权重
-		#[pallet::weight(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 5))]
 		// 创建并存储存证
 		pub fn create_claim(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			ensure!(proof.len() <= T::AssetDepositBase::get(), Error::<T>::ClaimTooLong);
-			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
-			let current_block = <frame_system::Pallet<T>>::block_number();
-			Proofs::<T>::insert(&proof, (&sender, current_block));
-			Self::deposit_event(Event::ClaimCreated(sender, proof));
-			Ok(())
+			Self::do_create_claim(sender, proof, None, None)
 		}
 
 
-		#[pallet::weight(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 5))]
 		// 销毁存证
 		pub fn revoke_claim(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 			ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
 			let (owner, _) = Proofs::<T>::get(&proof);
 			ensure!(sender == owner, Error::<T>::NotProofOwner);
-			Proofs::<T>::remove(&proof);
-			Self::deposit_event(Event::ClaimRevoked(sender, proof));
+			ensure!(!MultiPartyClaims::<T>::contains_key(&proof), Error::<T>::RequiresThreshold);
+			Self::revoke(owner, proof, 0);
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 5))]
+		// 附带原因代码销毁存证,吊销记录会保留原因
+		pub fn revoke_claim_with_reason(origin: OriginFor<T>, proof: Vec<u8>, reason: u8) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+			let (owner, _) = Proofs::<T>::get(&proof);
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			ensure!(!MultiPartyClaims::<T>::contains_key(&proof), Error::<T>::RequiresThreshold);
+			Self::revoke(owner, proof, reason);
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
 		// 转移存证
 		pub fn transfer_claim(origin: OriginFor<T>,claim: Vec<u8>,dest: T::AccountId) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
@@ -107,9 +358,430 @@ pub mod pallet {
 			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::NoSuchProof);
 			let (owner, _block_number) = Proofs::<T>::get(&claim);
 			ensure!(owner == sender, Error::<T>::NotProofOwner);
-			let current_block = <frame_system::Pallet<T>>::block_number();
-			Proofs::<T>::insert(&claim, (dest, current_block));
+			ensure!(!MultiPartyClaims::<T>::contains_key(&claim), Error::<T>::RequiresThreshold);
+			Self::do_transfer_claim(owner, claim, dest)?;
 			Ok(().into())
 		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 5))]
+		// 创建带元数据及可选过期区块的存证
+		pub fn create_claim_with_metadata(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			metadata: Option<ClaimMetadata<T>>,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::do_create_claim(sender, proof, metadata, expires_at)
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		// 存证续期(为永久存证设置过期区块时需要质押押金)
+		pub fn renew_claim(origin: OriginFor<T>, proof: Vec<u8>, expires_at: T::BlockNumber) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+			let (owner, _) = Proofs::<T>::get(&proof);
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			ensure!(!MultiPartyClaims::<T>::contains_key(&proof), Error::<T>::RequiresThreshold);
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(expires_at > current_block, Error::<T>::ExpiryInPast);
+
+			let deposit = match Expiries::<T>::get(&proof) {
+				Some(old) => {
+					ExpiringAt::<T>::remove(old.expires_at, &proof);
+					old.deposit
+				},
+				None => Self::reserve_expiry_deposit(&owner)?,
+			};
+			Expiries::<T>::insert(&proof, ClaimExpiry { expires_at, deposit });
+			ExpiringAt::<T>::insert(expires_at, &proof, ());
+
+			Self::deposit_event(Event::ClaimRenewed(sender, proof, expires_at));
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 5))]
+		// 任何账户都可以清理已过期的存证并获得奖励
+		pub fn prune_claim(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+			let expiry = Expiries::<T>::get(&proof).ok_or(Error::<T>::ClaimNotExpired)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(expiry.expires_at <= current_block, Error::<T>::ClaimNotExpired);
+
+			let (owner, _) = Proofs::<T>::get(&proof);
+			// 奖励从所有者的押金中支付,剩余部分在remove_claim中退还
+			let reward = T::PruneReward::get().min(expiry.deposit);
+			let reward = reward.saturating_sub(
+				T::Currency::repatriate_reserved(&owner, &sender, reward, BalanceStatus::Free)?,
+			);
+			Self::remove_claim(&owner, &proof);
+
+			Self::deposit_event(Event::ClaimPruned(Some(sender), proof, reward));
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		// 创建批量存证,只存储文档集合的Merkle根
+		pub fn create_batch_claim(origin: OriginFor<T>, root: H256, leaf_count: u32) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+			ensure!(!BatchProofs::<T>::contains_key(&root), Error::<T>::ProofAlreadyClaimed);
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			BatchProofs::<T>::insert(
+				&root,
+				BatchClaim {
+					owner: sender.clone(),
+					block_number: current_block,
+					timestamp: T::TimeProvider::now(),
+					leaf_count,
+				},
+			);
+			Self::deposit_event(Event::BatchClaimCreated(sender, root, leaf_count));
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		// 吊销批量存证
+		pub fn revoke_batch_claim(origin: OriginFor<T>, root: H256) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let batch = BatchProofs::<T>::get(&root).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == batch.owner, Error::<T>::NotProofOwner);
+			BatchProofs::<T>::remove(&root);
+			Self::deposit_event(Event::BatchClaimRevoked(sender, root));
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		// 提议多方存证,提议人自动成为签署人并计入一次确认
+		// signers: 共同签署人
+		// threshold: 生效所需的确认数
+		// deadline: 确认截止区块
+		pub fn propose_multi_party_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			signers: Vec<T::AccountId>,
+			threshold: u32,
+			deadline: T::BlockNumber,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(proof.len() <= T::AssetDepositBase::get(), Error::<T>::ClaimTooLong);
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			ensure!(!PendingClaims::<T>::contains_key(&proof), Error::<T>::ApprovalAlreadyPending);
+			Self::ensure_not_tombstoned(&sender, &proof)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(deadline > current_block, Error::<T>::DeadlinePassed);
+
+			let mut signers = signers;
+			signers.push(sender.clone());
+			signers.sort();
+			signers.dedup();
+			ensure!(threshold > 0 && threshold as usize <= signers.len(), Error::<T>::InvalidThreshold);
+			let signers = BoundedVec::try_from(signers).map_err(|_| Error::<T>::TooManySigners)?;
+			let approvals = BoundedVec::try_from(sp_std::vec![sender.clone()])
+				.map_err(|_| Error::<T>::TooManySigners)?;
+
+			let pending = PendingClaim { proposer: sender.clone(), signers, threshold, approvals, deadline };
+			Self::deposit_event(Event::MultiPartyClaimProposed(sender, proof.clone()));
+			if threshold == 1 {
+				Self::finalize_multi_party_claim(proof, pending)?;
+			} else {
+				PendingClaims::<T>::insert(&proof, pending);
+			}
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		// 共同签署人确认多方存证,达到门限后存证生效
+		pub fn approve_multi_party_claim(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let mut pending = PendingClaims::<T>::get(&proof).ok_or(Error::<T>::NoPendingApproval)?;
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(pending.deadline >= current_block, Error::<T>::DeadlinePassed);
+			ensure!(pending.signers.contains(&sender), Error::<T>::NotSigner);
+			ensure!(!pending.approvals.contains(&sender), Error::<T>::AlreadyApproved);
+			pending.approvals.try_push(sender.clone()).map_err(|_| Error::<T>::TooManySigners)?;
+
+			Self::deposit_event(Event::MultiPartyApproved(sender, proof.clone()));
+			if pending.approvals.len() as u32 >= pending.threshold {
+				PendingClaims::<T>::remove(&proof);
+				Self::finalize_multi_party_claim(proof, pending)?;
+			} else {
+				PendingClaims::<T>::insert(&proof, pending);
+			}
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		// 取消未生效的多方存证,提议人可随时取消,截止区块之后任何人都可以清理
+		pub fn cancel_multi_party_claim(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let pending = PendingClaims::<T>::get(&proof).ok_or(Error::<T>::NoPendingApproval)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				pending.proposer == sender || pending.deadline < current_block,
+				Error::<T>::NotProofOwner
+			);
+			PendingClaims::<T>::remove(&proof);
+			Self::deposit_event(Event::MultiPartyCancelled(proof));
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 4))]
+		// 签署人提议吊销或转移多方存证,提议人计入一次确认
+		pub fn propose_claim_action(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			action: ClaimAction<T::AccountId>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let claim = MultiPartyClaims::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(claim.signers.contains(&sender), Error::<T>::NotSigner);
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			// 已过截止区块的旧操作可以被新的提议覆盖
+			if let Some(old) = PendingActions::<T>::get(&proof) {
+				ensure!(old.deadline < current_block, Error::<T>::ApprovalAlreadyPending);
+			}
+
+			let approvals = BoundedVec::try_from(sp_std::vec![sender.clone()])
+				.map_err(|_| Error::<T>::TooManySigners)?;
+			let deadline = current_block.saturating_add(T::ActionPeriod::get());
+			let pending = PendingAction { action: action.clone(), approvals, deadline };
+			Self::deposit_event(Event::ClaimActionProposed(sender, proof.clone(), action));
+			if claim.threshold <= 1 {
+				PendingActions::<T>::remove(&proof);
+				Self::execute_claim_action(proof, pending.action)?;
+			} else {
+				PendingActions::<T>::insert(&proof, pending);
+			}
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 4))]
+		// 签署人确认多方存证操作,达到门限后执行
+		pub fn approve_claim_action(origin: OriginFor<T>, proof: Vec<u8>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let claim = MultiPartyClaims::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(claim.signers.contains(&sender), Error::<T>::NotSigner);
+			let mut pending = PendingActions::<T>::get(&proof).ok_or(Error::<T>::NoPendingApproval)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(pending.deadline >= current_block, Error::<T>::DeadlinePassed);
+			ensure!(!pending.approvals.contains(&sender), Error::<T>::AlreadyApproved);
+			pending.approvals.try_push(sender.clone()).map_err(|_| Error::<T>::TooManySigners)?;
+
+			Self::deposit_event(Event::MultiPartyApproved(sender, proof.clone()));
+			if pending.approvals.len() as u32 >= claim.threshold {
+				PendingActions::<T>::remove(&proof);
+				Self::execute_claim_action(proof, pending.action)?;
+			} else {
+				PendingActions::<T>::insert(&proof, pending);
+			}
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 校验文档哈希是否包含在已存证的批量存证中
+		/// root: 批量存证的Merkle根
+		/// leaf: 文档哈希
+		/// index: 文档在批量存证中的位置,必须小于存证时登记的叶子数
+		/// proof: 从叶子到根的兄弟节点哈希,按index决定左右顺序,长度必须与该位置在树中的深度一致
+		pub fn verify_inclusion(root: H256, leaf: H256, index: u32, proof: Vec<H256>) -> bool {
+			let batch = match BatchProofs::<T>::get(&root) {
+				Some(batch) => batch,
+				None => return false,
+			};
+			crate::merkle::verify_proof(&root, &leaf, index, batch.leaf_count, &proof)
+		}
+
+		/// 查询存证的所有者、区块和时间戳
+		pub fn proof_of(proof: &[u8]) -> Option<(T::AccountId, T::BlockNumber, MomentOf<T>)> {
+			let (owner, block_number) = Proofs::<T>::try_get(proof).ok()?;
+			let timestamp = ClaimTimestamps::<T>::get(proof).unwrap_or_default();
+			Some((owner, block_number, timestamp))
+		}
+
+		/// 存证相关存储项的完整键,用于生成可离线校验的存储证明
+		pub fn claim_storage_keys(proof: &[u8]) -> Vec<Vec<u8>> {
+			sp_std::vec![Proofs::<T>::hashed_key_for(proof), ClaimTimestamps::<T>::hashed_key_for(proof)]
+		}
+
+		/// 记录存证(所有者、当前区块及时间戳)
+		fn record_claim(owner: &T::AccountId, proof: &[u8]) {
+			if let Some(tombstone) = Tombstones::<T>::take(proof) {
+				let expires_at = tombstone.block_number.saturating_add(T::TombstonePeriod::get());
+				TombstonesExpiringAt::<T>::remove(expires_at, proof);
+			}
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			Proofs::<T>::insert(proof, (owner, current_block));
+			ClaimTimestamps::<T>::insert(proof, T::TimeProvider::now());
+		}
+
+		/// 创建存证的公共逻辑
+		fn do_create_claim(
+			sender: T::AccountId,
+			proof: Vec<u8>,
+			metadata: Option<ClaimMetadata<T>>,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			ensure!(proof.len() <= T::AssetDepositBase::get(), Error::<T>::ClaimTooLong);
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			// 多方存证确认期间其他人不能抢先注册同一存证
+			ensure!(!PendingClaims::<T>::contains_key(&proof), Error::<T>::ApprovalAlreadyPending);
+			Self::ensure_not_tombstoned(&sender, &proof)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+
+			if let Some(expires_at) = expires_at {
+				ensure!(expires_at > current_block, Error::<T>::ExpiryInPast);
+				let deposit = Self::reserve_expiry_deposit(&sender)?;
+				Expiries::<T>::insert(&proof, ClaimExpiry { expires_at, deposit });
+				ExpiringAt::<T>::insert(expires_at, &proof, ());
+			}
+			if let Some(metadata) = metadata {
+				Metadata::<T>::insert(&proof, metadata);
+			}
+
+			Self::record_claim(&sender, &proof);
+			Self::deposit_event(Event::ClaimCreated(sender, proof));
+			Ok(())
+		}
+
+		/// 多方存证达到门限后生效,存证所有者记为提议人
+		fn finalize_multi_party_claim(proof: Vec<u8>, pending: PendingClaim<T>) -> DispatchResult {
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			Self::record_claim(&pending.proposer, &proof);
+			MultiPartyClaims::<T>::insert(
+				&proof,
+				MultiPartyClaim { signers: pending.signers, threshold: pending.threshold },
+			);
+			Self::deposit_event(Event::ClaimCreated(pending.proposer, proof));
+			Ok(())
+		}
+
+		/// 执行已达到门限的多方存证操作
+		fn execute_claim_action(proof: Vec<u8>, action: ClaimAction<T::AccountId>) -> DispatchResult {
+			let (owner, _) = Proofs::<T>::get(&proof);
+			MultiPartyClaims::<T>::remove(&proof);
+			match action {
+				ClaimAction::Revoke(reason) => Self::revoke(owner, proof, reason),
+				ClaimAction::Transfer(dest) => Self::do_transfer_claim(owner, proof, dest)?,
+			}
+			Ok(())
+		}
+
+		/// 吊销存证,保护期不为0时保留吊销记录,保护期结束后由on_idle清理
+		fn revoke(owner: T::AccountId, proof: Vec<u8>, reason: u8) {
+			Self::remove_claim(&owner, &proof);
+			let period = T::TombstonePeriod::get();
+			if !period.is_zero() {
+				let block_number = <frame_system::Pallet<T>>::block_number();
+				Tombstones::<T>::insert(&proof, Tombstone { revoker: owner.clone(), block_number, reason });
+				TombstonesExpiringAt::<T>::insert(block_number.saturating_add(period), &proof, ());
+			}
+			if reason == 0 {
+				Self::deposit_event(Event::ClaimRevoked(owner, proof));
+			} else {
+				Self::deposit_event(Event::ClaimRevokedWithReason(owner, proof, reason));
+			}
+		}
+
+		/// 检查吊销记录,保护期内只有原吊销人可以重新注册;重新注册时清除吊销记录
+		fn ensure_not_tombstoned(who: &T::AccountId, proof: &[u8]) -> DispatchResult {
+			if let Some(tombstone) = Tombstones::<T>::get(proof) {
+				let current_block = <frame_system::Pallet<T>>::block_number();
+				let protected_until = tombstone.block_number.saturating_add(T::TombstonePeriod::get());
+				ensure!(
+					&tombstone.revoker == who || current_block >= protected_until,
+					Error::<T>::ProofTombstoned
+				);
+			}
+			Ok(())
+		}
+
+		/// 转移存证的公共逻辑,过期押金随存证一起转移给新所有者
+		fn do_transfer_claim(owner: T::AccountId, proof: Vec<u8>, dest: T::AccountId) -> DispatchResult {
+			if let Some(expiry) = Expiries::<T>::get(&proof) {
+				T::Currency::repatriate_reserved(&owner, &dest, expiry.deposit, BalanceStatus::Reserved)?;
+			}
+			Self::record_claim(&dest, &proof);
+			Self::deposit_event(Event::ClaimTransferred(owner, dest, proof));
+			Ok(())
+		}
+
+		/// 质押过期押金
+		fn reserve_expiry_deposit(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+			let deposit = T::ExpiryDeposit::get();
+			T::Currency::reserve(who, deposit).map_err(|_| Error::<T>::NotEnoughBalanceForDeposit)?;
+			Ok(deposit)
+		}
+
+		/// 删除存证及其元数据、过期信息,并退还剩余押金
+		fn remove_claim(owner: &T::AccountId, proof: &[u8]) {
+			if let Some(expiry) = Expiries::<T>::take(proof) {
+				ExpiringAt::<T>::remove(expiry.expires_at, proof);
+				T::Currency::unreserve(owner, expiry.deposit);
+			}
+			Metadata::<T>::remove(proof);
+			ClaimTimestamps::<T>::remove(proof);
+			Proofs::<T>::remove(proof);
+		}
+
+		/// 从PruneCursor开始依次清理到期的存证和保护期已过的吊销记录,直到剩余权重不足
+		/// now: 当前区块
+		/// remaining_weight: 区块剩余可用权重
+		pub(crate) fn prune_expired(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			// 每条存证: 读取存证和过期信息,删除4个存储项并退还押金
+			let per_claim = db.reads_writes(3, 5);
+			// 每条吊销记录: 删除记录及索引
+			let per_tombstone = db.writes(2);
+			// 读写游标
+			let mut used = db.reads_writes(1, 1);
+			if used > remaining_weight {
+				return 0
+			}
+
+			let mut cursor = PruneCursor::<T>::get();
+			while cursor <= now {
+				// 空的区块前缀同样消耗读取权重(存证和吊销记录各一次),预算用完立即停止
+				if used.saturating_add(db.reads(2)) > remaining_weight {
+					break
+				}
+				used = used.saturating_add(db.reads(2));
+				let budget = (remaining_weight.saturating_sub(used) / per_claim.max(1)) as usize;
+				// 先收集再删除,避免在迭代过程中修改同一前缀
+				let expired: Vec<Vec<u8>> =
+					ExpiringAt::<T>::iter_key_prefix(cursor).take(budget.saturating_add(1)).collect();
+				let finished = expired.len() <= budget;
+				for proof in expired.into_iter().take(budget) {
+					used = used.saturating_add(per_claim);
+					let (owner, _) = Proofs::<T>::get(&proof);
+					Self::remove_claim(&owner, &proof);
+					Self::deposit_event(Event::ClaimPruned(None, proof, Zero::zero()));
+				}
+				if !finished {
+					break
+				}
+
+				let budget = (remaining_weight.saturating_sub(used) / per_tombstone.max(1)) as usize;
+				let expired: Vec<Vec<u8>> =
+					TombstonesExpiringAt::<T>::iter_key_prefix(cursor).take(budget.saturating_add(1)).collect();
+				let finished = expired.len() <= budget;
+				for proof in expired.into_iter().take(budget) {
+					used = used.saturating_add(per_tombstone);
+					TombstonesExpiringAt::<T>::remove(cursor, &proof);
+					Tombstones::<T>::remove(&proof);
+				}
+				if !finished {
+					break
+				}
+				cursor = cursor.saturating_add(One::one());
+			}
+			PruneCursor::<T>::put(cursor);
+			used
+		}
 	}
 }
@@ -0,0 +1,103 @@
+//! 批量存证使用的Merkle树
+//!
+//! 叶子节点为 `blake2_256(0x00 ++ 文档哈希)`，内部节点为 `blake2_256(0x01 ++ 左 ++ 右)`，
+//! 校验时由叶子索引的每一位决定兄弟节点在左还是在右，因此证明同时绑定了叶子的位置。
+//! 某一层节点数为奇数时，最后一个节点直接提升到上一层。
+//!
+//! 该模块在runtime和节点端共用，保证链上校验与链下生成证明的规则一致。
+
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// 计算叶子节点哈希
+pub fn leaf_hash(leaf: &H256) -> H256 {
+	let mut data = [0u8; 33];
+	data[0] = LEAF_PREFIX;
+	data[1..].copy_from_slice(leaf.as_bytes());
+	H256(blake2_256(&data))
+}
+
+/// 计算左右两个子节点的父节点哈希
+pub fn node_hash(left: &H256, right: &H256) -> H256 {
+	let mut data = [0u8; 65];
+	data[0] = NODE_PREFIX;
+	data[1..33].copy_from_slice(left.as_bytes());
+	data[33..].copy_from_slice(right.as_bytes());
+	H256(blake2_256(&data))
+}
+
+/// 根据文档哈希构建每一层的节点,第一层为叶子节点,最后一层为根
+pub fn build_layers(leaves: &[H256]) -> Vec<Vec<H256>> {
+	let mut layers = Vec::new();
+	let mut layer: Vec<H256> = leaves.iter().map(leaf_hash).collect();
+	while layer.len() > 1 {
+		let next = layer
+			.chunks(2)
+			.map(|pair| match pair {
+				[a, b] => node_hash(a, b),
+				[a] => *a,
+				_ => unreachable!("chunks(2) yields one or two items; qed"),
+			})
+			.collect();
+		layers.push(layer);
+		layer = next;
+	}
+	layers.push(layer);
+	layers
+}
+
+/// 计算Merkle根,没有叶子时返回None
+pub fn merkle_root(leaves: &[H256]) -> Option<H256> {
+	build_layers(leaves).last().and_then(|root| root.first().copied())
+}
+
+/// 根据已构建的层生成第index个叶子的证明
+pub fn merkle_proof(layers: &[Vec<H256>], mut index: usize) -> Vec<H256> {
+	let mut proof = Vec::new();
+	for layer in layers.iter().take(layers.len().saturating_sub(1)) {
+		if let Some(sibling) = layer.get(index ^ 1) {
+			proof.push(*sibling);
+		}
+		index /= 2;
+	}
+	proof
+}
+
+/// 校验文档哈希是否为以root为根、共有leaf_count个叶子的Merkle树中的第index个叶子
+///
+/// 每一层按索引的奇偶决定兄弟节点的位置;奇数层的最后一个节点直接提升,不消耗证明中的节点,
+/// 因此证明长度必须与该位置在树中的深度完全一致。
+pub fn verify_proof(
+	root: &H256,
+	leaf: &H256,
+	mut index: u32,
+	leaf_count: u32,
+	proof: &[H256],
+) -> bool {
+	if index >= leaf_count {
+		return false
+	}
+	let mut siblings = proof.iter();
+	let mut computed = leaf_hash(leaf);
+	let mut width = leaf_count;
+	while width > 1 {
+		if index ^ 1 < width {
+			let sibling = match siblings.next() {
+				Some(sibling) => sibling,
+				None => return false,
+			};
+			computed = if index % 2 == 0 {
+				node_hash(&computed, sibling)
+			} else {
+				node_hash(sibling, &computed)
+			};
+		}
+		index /= 2;
+		width = (width + 1) / 2;
+	}
+	siblings.next().is_none() && &computed == root
+}
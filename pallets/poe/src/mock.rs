@@ -1,5 +1,5 @@
 use crate as pallet_poe;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::Time};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -9,6 +9,7 @@ use sp_runtime::{
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
+type Balance = u128;
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -19,6 +20,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		PoeModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -46,7 +48,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<Balance>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -54,12 +56,70 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 500;
+	pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = MaxLocks;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MaxMetadataLength: u32 = 16;
+	pub const ExpiryDeposit: u128 = 1_000;
+	pub const PruneReward: u128 = 100;
+	pub const MaxSigners: u32 = 3;
+	pub const ActionPeriod: u64 = 10;
+	pub const TombstonePeriod: u64 = 20;
+}
+
+pub const MILLISECS_PER_BLOCK: u64 = 6000;
+
+/// 以区块高度推算时间戳,避免在测试中引入pallet_timestamp
+pub struct MockTime;
+
+impl Time for MockTime {
+	type Moment = u64;
+
+	fn now() -> u64 {
+		System::block_number() * MILLISECS_PER_BLOCK
+	}
+}
+
 impl pallet_poe::Config for Test {
 	type Event = Event;
 	type AssetDepositBase = AssetDepositBase;
+	type TimeProvider = MockTime;
+	type Currency = Balances;
+	type MaxMetadataLength = MaxMetadataLength;
+	type ExpiryDeposit = ExpiryDeposit;
+	type PruneReward = PruneReward;
+	type MaxSigners = MaxSigners;
+	type ActionPeriod = ActionPeriod;
+	type TombstonePeriod = TombstonePeriod;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	// Genesis funds
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 10_000), (2, 10_000), (3, 10_000), (4, 500)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
@@ -1,6 +1,10 @@
 use super::*;
 use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GetStorageVersion, Hooks},
+	weights::Weight,
+};
 
 #[test]
 fn create_claim_works() {
@@ -108,3 +112,505 @@ fn create_claim_failed_when_claim_too_long() {
 		);
 	})
 }
+
+fn metadata_of(mime_type: &[u8], description: &[u8], uri: &[u8]) -> ClaimMetadata<Test> {
+	ClaimMetadata {
+		mime_type: mime_type.to_vec().try_into().unwrap(),
+		description: description.to_vec().try_into().unwrap(),
+		uri: uri.to_vec().try_into().unwrap(),
+	}
+}
+
+#[test]
+fn create_claim_with_metadata_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let metadata = metadata_of(b"text/plain", b"contract", b"ipfs://x");
+		assert_ok!(PoeModule::create_claim_with_metadata(
+			Origin::signed(1),
+			claim.clone(),
+			Some(metadata.clone()),
+			Some(10)
+		));
+		assert_eq!(Metadata::<Test>::get(&claim), Some(metadata));
+		assert_eq!(Expiries::<Test>::get(&claim).map(|e| e.expires_at), Some(10));
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+	})
+}
+
+#[test]
+fn create_claim_with_expiry_failed_when_expiry_in_past() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PoeModule::create_claim_with_metadata(Origin::signed(1), vec![0, 1], None, Some(1)),
+			Error::<Test>::ExpiryInPast
+		);
+	})
+}
+
+#[test]
+fn create_claim_with_expiry_failed_when_balance_too_low() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PoeModule::create_claim_with_metadata(Origin::signed(4), vec![0, 1], None, Some(10)),
+			Error::<Test>::NotEnoughBalanceForDeposit
+		);
+	})
+}
+
+#[test]
+fn renew_claim_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim.clone(), None, Some(10));
+		assert_ok!(PoeModule::renew_claim(Origin::signed(1), claim.clone(), 20));
+		assert_eq!(Expiries::<Test>::get(&claim).map(|e| e.expires_at), Some(20));
+		assert!(!ExpiringAt::<Test>::contains_key(10, &claim));
+		assert!(ExpiringAt::<Test>::contains_key(20, &claim));
+		// 续期不会重复质押押金
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert_noop!(
+			PoeModule::renew_claim(Origin::signed(2), claim, 30),
+			Error::<Test>::NotProofOwner
+		);
+	})
+}
+
+#[test]
+fn prune_claim_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim.clone(), None, Some(10));
+		assert_noop!(
+			PoeModule::prune_claim(Origin::signed(2), claim.clone()),
+			Error::<Test>::ClaimNotExpired
+		);
+
+		System::set_block_number(10);
+		assert_ok!(PoeModule::prune_claim(Origin::signed(2), claim.clone()));
+		assert_eq!(Proofs::<Test>::contains_key(&claim), false);
+		assert_eq!(Expiries::<Test>::contains_key(&claim), false);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 9_900);
+		assert_eq!(Balances::free_balance(2), 10_100);
+	})
+}
+
+#[test]
+fn prune_claim_failed_when_claim_has_no_expiry() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		assert_noop!(PoeModule::prune_claim(Origin::signed(2), claim), Error::<Test>::ClaimNotExpired);
+	})
+}
+
+#[test]
+fn on_idle_prunes_expired_claims() {
+	new_test_ext().execute_with(|| {
+		let claim_red = vec![0, 1];
+		let claim_blue = vec![2, 1];
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim_red.clone(), None, Some(5));
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim_blue.clone(), None, Some(8));
+
+		PoeModule::on_idle(6, Weight::max_value());
+		assert_eq!(Proofs::<Test>::contains_key(&claim_red), false);
+		assert_eq!(Proofs::<Test>::contains_key(&claim_blue), true);
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert_eq!(PruneCursor::<Test>::get(), 7);
+	})
+}
+
+#[test]
+fn on_idle_respects_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim.clone(), None, Some(5));
+
+		assert_eq!(PoeModule::on_idle(6, 0), 0);
+		assert_eq!(Proofs::<Test>::contains_key(&claim), true);
+	})
+}
+
+#[test]
+fn runtime_upgrade_starts_prune_cursor_at_current_block() {
+	new_test_ext().execute_with(|| {
+		frame_support::traits::StorageVersion::new(0).put::<PoeModule>();
+		System::set_block_number(100);
+		PoeModule::on_runtime_upgrade();
+		assert_eq!(PruneCursor::<Test>::get(), 100);
+		assert_eq!(PoeModule::on_chain_storage_version(), 2);
+
+		// 再次升级不会移动游标
+		System::set_block_number(200);
+		PoeModule::on_runtime_upgrade();
+		assert_eq!(PruneCursor::<Test>::get(), 100);
+	})
+}
+
+#[test]
+fn revoke_claim_unreserves_expiry_deposit() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim_with_metadata(Origin::signed(1), claim.clone(), None, Some(10));
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert!(!ExpiringAt::<Test>::contains_key(10, &claim));
+	})
+}
+
+fn documents(count: u8) -> Vec<sp_core::H256> {
+	(0..count).map(|i| sp_core::H256::repeat_byte(i)).collect()
+}
+
+#[test]
+fn merkle_proof_verifies_every_leaf() {
+	for count in 1..=9u32 {
+		let leaves = documents(count as u8);
+		let layers = crate::merkle::build_layers(&leaves);
+		let root = crate::merkle::merkle_root(&leaves).unwrap();
+		let depth = count.next_power_of_two().trailing_zeros() as usize;
+		for (index, leaf) in leaves.iter().enumerate() {
+			let proof = crate::merkle::merkle_proof(&layers, index);
+			assert!(proof.len() <= depth);
+			assert!(crate::merkle::verify_proof(&root, leaf, index as u32, count, &proof));
+			// 同一证明不能用于其他位置
+			for other in (0..count).filter(|other| *other != index as u32) {
+				assert!(!crate::merkle::verify_proof(&root, leaf, other, count, &proof));
+			}
+		}
+		assert!(!crate::merkle::verify_proof(&root, &leaves[0], count, count, &[]));
+		assert!(!crate::merkle::verify_proof(&root, &sp_core::H256::repeat_byte(0xff), 0, count, &[]));
+	}
+}
+
+#[test]
+fn create_batch_claim_works() {
+	new_test_ext().execute_with(|| {
+		let leaves = documents(5);
+		let layers = crate::merkle::build_layers(&leaves);
+		let root = crate::merkle::merkle_root(&leaves).unwrap();
+		assert_ok!(PoeModule::create_batch_claim(Origin::signed(1), root, 5));
+		assert_eq!(BatchProofs::<Test>::get(&root).map(|b| (b.owner, b.leaf_count)), Some((1, 5)));
+
+		let proof = crate::merkle::merkle_proof(&layers, 3);
+		assert!(PoeModule::verify_inclusion(root, leaves[3], 3, proof.clone()));
+		assert!(!PoeModule::verify_inclusion(root, leaves[2], 3, proof));
+		assert_noop!(
+			PoeModule::create_batch_claim(Origin::signed(2), root, 5),
+			Error::<Test>::ProofAlreadyClaimed
+		);
+	})
+}
+
+#[test]
+fn create_batch_claim_failed_when_batch_is_empty() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PoeModule::create_batch_claim(Origin::signed(1), sp_core::H256::zero(), 0),
+			Error::<Test>::EmptyBatch
+		);
+	})
+}
+
+#[test]
+fn revoke_batch_claim_works() {
+	new_test_ext().execute_with(|| {
+		let leaves = documents(2);
+		let root = crate::merkle::merkle_root(&leaves).unwrap();
+		let _ = PoeModule::create_batch_claim(Origin::signed(1), root, 2);
+		assert_noop!(
+			PoeModule::revoke_batch_claim(Origin::signed(2), root),
+			Error::<Test>::NotProofOwner
+		);
+		assert_ok!(PoeModule::revoke_batch_claim(Origin::signed(1), root));
+		let proof = crate::merkle::merkle_proof(&crate::merkle::build_layers(&leaves), 0);
+		assert!(!PoeModule::verify_inclusion(root, leaves[0], 0, proof));
+	})
+}
+
+#[test]
+fn verify_inclusion_checks_leaf_position() {
+	new_test_ext().execute_with(|| {
+		let leaves = documents(5);
+		let layers = crate::merkle::build_layers(&leaves);
+		let root = crate::merkle::merkle_root(&leaves).unwrap();
+		assert_ok!(PoeModule::create_batch_claim(Origin::signed(1), root, 5));
+
+		// 第5个叶子在奇数层直接提升,证明只有一个兄弟节点
+		let proof = crate::merkle::merkle_proof(&layers, 4);
+		assert_eq!(proof.len(), 1);
+		assert!(PoeModule::verify_inclusion(root, leaves[4], 4, proof.clone()));
+		assert!(!PoeModule::verify_inclusion(root, leaves[4], 5, proof.clone()));
+		assert!(!PoeModule::verify_inclusion(root, leaves[4], 0, proof));
+
+		// 深度相同的其他位置也不能复用该证明
+		let proof = crate::merkle::merkle_proof(&layers, 1);
+		assert!(PoeModule::verify_inclusion(root, leaves[1], 1, proof.clone()));
+		assert!(!PoeModule::verify_inclusion(root, leaves[1], 0, proof.clone()));
+		assert!(!PoeModule::verify_inclusion(root, leaves[1], 3, proof));
+
+		// 长度与叶子位置不符的证明被拒绝
+		let mut proof = crate::merkle::merkle_proof(&layers, 0);
+		proof.push(sp_core::H256::repeat_byte(1));
+		assert!(!PoeModule::verify_inclusion(root, leaves[0], 0, proof));
+	})
+}
+
+#[test]
+fn multi_party_claim_becomes_final_at_threshold() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		assert_ok!(PoeModule::propose_multi_party_claim(
+			Origin::signed(1),
+			claim.clone(),
+			vec![2, 3],
+			2,
+			10
+		));
+		assert_eq!(Proofs::<Test>::contains_key(&claim), false);
+		assert_noop!(
+			PoeModule::approve_multi_party_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::AlreadyApproved
+		);
+		assert_noop!(
+			PoeModule::approve_multi_party_claim(Origin::signed(4), claim.clone()),
+			Error::<Test>::NotSigner
+		);
+
+		assert_ok!(PoeModule::approve_multi_party_claim(Origin::signed(3), claim.clone()));
+		assert_eq!(Proofs::<Test>::get(&claim), (1, 1));
+		assert_eq!(PendingClaims::<Test>::contains_key(&claim), false);
+		assert_eq!(MultiPartyClaims::<Test>::get(&claim).map(|c| c.threshold), Some(2));
+	})
+}
+
+#[test]
+fn create_claim_failed_while_multi_party_claim_pending() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		assert_ok!(PoeModule::propose_multi_party_claim(Origin::signed(1), claim.clone(), vec![2], 2, 10));
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(3), claim.clone()),
+			Error::<Test>::ApprovalAlreadyPending
+		);
+		assert_noop!(
+			PoeModule::create_claim_with_metadata(Origin::signed(3), claim.clone(), None, Some(5)),
+			Error::<Test>::ApprovalAlreadyPending
+		);
+
+		// 确认期间存证已被登记(例如升级前的数据)时,确认失败且不覆盖原存证
+		Proofs::<Test>::insert(&claim, (3, 1));
+		assert_noop!(
+			PoeModule::approve_multi_party_claim(Origin::signed(2), claim.clone()),
+			Error::<Test>::ProofAlreadyClaimed
+		);
+		assert_eq!(Proofs::<Test>::get(&claim).0, 3);
+		assert_eq!(MultiPartyClaims::<Test>::contains_key(&claim), false);
+
+		Proofs::<Test>::remove(&claim);
+		assert_ok!(PoeModule::approve_multi_party_claim(Origin::signed(2), claim.clone()));
+		assert_eq!(Proofs::<Test>::get(&claim).0, 1);
+		assert_eq!(Expiries::<Test>::contains_key(&claim), false);
+	})
+}
+
+#[test]
+fn propose_multi_party_claim_failed_when_threshold_invalid() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PoeModule::propose_multi_party_claim(Origin::signed(1), vec![0, 1], vec![2], 3, 10),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			PoeModule::propose_multi_party_claim(Origin::signed(1), vec![0, 1], vec![2, 3, 4], 2, 10),
+			Error::<Test>::TooManySigners
+		);
+	})
+}
+
+#[test]
+fn approve_multi_party_claim_failed_after_deadline() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::propose_multi_party_claim(Origin::signed(1), claim.clone(), vec![2], 2, 5);
+		System::set_block_number(6);
+		assert_noop!(
+			PoeModule::approve_multi_party_claim(Origin::signed(2), claim.clone()),
+			Error::<Test>::DeadlinePassed
+		);
+		// 截止之后任何人都可以清理
+		assert_ok!(PoeModule::cancel_multi_party_claim(Origin::signed(3), claim.clone()));
+		assert_eq!(PendingClaims::<Test>::contains_key(&claim), false);
+	})
+}
+
+#[test]
+fn multi_party_claim_requires_threshold_to_revoke() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::propose_multi_party_claim(Origin::signed(1), claim.clone(), vec![2], 2, 10);
+		let _ = PoeModule::approve_multi_party_claim(Origin::signed(2), claim.clone());
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::RequiresThreshold
+		);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 3),
+			Error::<Test>::RequiresThreshold
+		);
+
+		assert_ok!(PoeModule::propose_claim_action(Origin::signed(1), claim.clone(), ClaimAction::Revoke(0)));
+		assert_eq!(Proofs::<Test>::contains_key(&claim), true);
+		assert_ok!(PoeModule::approve_claim_action(Origin::signed(2), claim.clone()));
+		assert_eq!(Proofs::<Test>::contains_key(&claim), false);
+		assert_eq!(MultiPartyClaims::<Test>::contains_key(&claim), false);
+	})
+}
+
+#[test]
+fn multi_party_claim_transfer_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::propose_multi_party_claim(Origin::signed(1), claim.clone(), vec![2], 2, 10);
+		let _ = PoeModule::approve_multi_party_claim(Origin::signed(2), claim.clone());
+		assert_ok!(PoeModule::propose_claim_action(
+			Origin::signed(2),
+			claim.clone(),
+			ClaimAction::Transfer(3)
+		));
+		assert_noop!(
+			PoeModule::propose_claim_action(Origin::signed(1), claim.clone(), ClaimAction::Revoke(0)),
+			Error::<Test>::ApprovalAlreadyPending
+		);
+		assert_ok!(PoeModule::approve_claim_action(Origin::signed(1), claim.clone()));
+		assert_eq!(Proofs::<Test>::get(&claim).0, 3);
+		// 转移后变为单一所有者存证
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(3), claim));
+	})
+}
+
+#[test]
+fn create_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		System::set_block_number(3);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_eq!(ClaimTimestamps::<Test>::get(&claim), Some(3 * MILLISECS_PER_BLOCK));
+		assert_eq!(PoeModule::proof_of(&claim), Some((1, 3, 3 * MILLISECS_PER_BLOCK)));
+		assert_eq!(PoeModule::proof_of(&[9]), None);
+
+		System::set_block_number(5);
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+		assert_eq!(PoeModule::proof_of(&claim), Some((2, 5, 5 * MILLISECS_PER_BLOCK)));
+
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(2), claim.clone()));
+		assert_eq!(ClaimTimestamps::<Test>::contains_key(&claim), false);
+	})
+}
+
+#[test]
+fn claim_storage_keys_match_storage() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let keys = PoeModule::claim_storage_keys(&claim);
+		assert_eq!(keys.len(), 2);
+		assert!(sp_io::storage::get(&keys[0]).is_some());
+		assert!(sp_io::storage::get(&keys[1]).is_some());
+	})
+}
+
+#[test]
+fn revoke_claim_leaves_tombstone() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		assert_ok!(PoeModule::revoke_claim_with_reason(Origin::signed(1), claim.clone(), 7));
+		assert_eq!(
+			Tombstones::<Test>::get(&claim).map(|t| (t.revoker, t.block_number, t.reason)),
+			Some((1, 1, 7))
+		);
+		System::assert_has_event(crate::mock::Event::PoeModule(crate::Event::ClaimRevokedWithReason(
+			1, claim, 7,
+		)));
+	})
+}
+
+#[test]
+fn create_claim_failed_when_tombstoned() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim.clone());
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), claim.clone()),
+			Error::<Test>::ProofTombstoned
+		);
+		assert_noop!(
+			PoeModule::propose_multi_party_claim(Origin::signed(2), claim.clone(), vec![3], 2, 10),
+			Error::<Test>::ProofTombstoned
+		);
+
+		// 原吊销人可以在保护期内重新注册,并清除吊销记录
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_eq!(Tombstones::<Test>::contains_key(&claim), false);
+	})
+}
+
+#[test]
+fn on_idle_prunes_tombstones_after_period() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim.clone());
+		assert!(TombstonesExpiringAt::<Test>::contains_key(21, &claim));
+
+		PoeModule::on_idle(20, Weight::max_value());
+		assert_eq!(Tombstones::<Test>::contains_key(&claim), true);
+		PoeModule::on_idle(21, Weight::max_value());
+		assert_eq!(Tombstones::<Test>::contains_key(&claim), false);
+		assert!(!TombstonesExpiringAt::<Test>::contains_key(21, &claim));
+		assert_eq!(PruneCursor::<Test>::get(), 22);
+	})
+}
+
+#[test]
+fn reclaiming_removes_tombstone_index() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim.clone());
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert!(!TombstonesExpiringAt::<Test>::contains_key(21, &claim));
+	})
+}
+
+#[test]
+fn runtime_upgrade_indexes_existing_tombstones() {
+	new_test_ext().execute_with(|| {
+		let (old, recent) = (vec![0, 1], vec![2, 1]);
+		Tombstones::<Test>::insert(&old, Tombstone { revoker: 1, block_number: 1, reason: 0 });
+		Tombstones::<Test>::insert(&recent, Tombstone { revoker: 1, block_number: 10, reason: 0 });
+		frame_support::traits::StorageVersion::new(1).put::<PoeModule>();
+		System::set_block_number(25);
+		PoeModule::on_runtime_upgrade();
+
+		// 保护期已过的直接删除,其余按保护期结束区块索引
+		assert_eq!(Tombstones::<Test>::contains_key(&old), false);
+		assert!(TombstonesExpiringAt::<Test>::contains_key(30, &recent));
+		assert_eq!(PoeModule::on_chain_storage_version(), 2);
+	})
+}
+
+#[test]
+fn create_claim_works_after_tombstone_period() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim.clone());
+		System::set_block_number(21);
+		assert_ok!(PoeModule::create_claim(Origin::signed(2), claim.clone()));
+		assert_eq!(Proofs::<Test>::get(&claim).0, 2);
+	})
+}
@@ -0,0 +1,120 @@
+//! 存证的存储证明校验库
+//!
+//! 轻客户端(如移动端)在不运行全节点的情况下,根据一个已最终确定的区块头和
+//! `read_proof` 返回的存储证明,校验某个存证的所有者、区块及时间戳。
+//!
+//! 区块头本身是否可信(例如通过GRANDPA最终性证明校验)由调用方负责。
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use sp_core::hashing::{blake2_128, twox_128};
+use sp_runtime::traits::Header as HeaderT;
+use sp_std::vec::Vec;
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+/// 存证所有者及区块所在的存储项名称
+pub const PROOFS_STORAGE: &[u8] = b"Proofs";
+/// 存证时间戳所在的存储项名称
+pub const TIMESTAMPS_STORAGE: &[u8] = b"ClaimTimestamps";
+
+/// 校验通过的存证
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct VerifiedClaim<AccountId, BlockNumber, Moment> {
+	/// 存证所有者
+	pub owner: AccountId,
+	/// 存证所在区块
+	pub block_number: BlockNumber,
+	/// 存证时间戳,状态中没有时间戳(如旧存证)时为None
+	pub timestamp: Option<Moment>,
+}
+
+/// 校验失败的原因
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Error {
+	/// 存储证明与区块头的状态根不匹配或缺少节点
+	InvalidProof,
+	/// 在该区块的状态中不存在此存证
+	ClaimNotFound,
+	/// 存储值无法解码
+	Decode,
+	/// 存证所有者及区块校验通过,但时间戳的存储证明缺少节点
+	InvalidTimestampProof,
+}
+
+/// 节点导出的存证证明包,可整体保存为文件供离线校验
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+pub struct ProofBundle<Header> {
+	/// 已最终确定的区块头
+	pub header: Header,
+	/// runtime中存证模块的名称(如 `PoeModule`)
+	pub pallet_name: Vec<u8>,
+	/// 存证内容
+	pub claim: Vec<u8>,
+	/// 存储证明的trie节点
+	pub proof: Vec<Vec<u8>>,
+}
+
+/// 计算 `Blake2_128Concat` 哈希的存储映射键
+pub fn storage_map_key(pallet_name: &[u8], storage_name: &[u8], claim: &[u8]) -> Vec<u8> {
+	let encoded = claim.encode();
+	let mut key = Vec::with_capacity(32 + 16 + encoded.len());
+	key.extend_from_slice(&twox_128(pallet_name));
+	key.extend_from_slice(&twox_128(storage_name));
+	key.extend_from_slice(&blake2_128(&encoded));
+	key.extend_from_slice(&encoded);
+	key
+}
+
+/// 根据区块头和存储证明校验存证
+/// header: 已最终确定的区块头
+/// proof: 包含存证存储项的存储证明
+/// pallet_name: runtime中存证模块的名称
+/// claim: 存证内容
+pub fn verify_claim<H, AccountId, BlockNumber, Moment>(
+	header: &H,
+	proof: StorageProof,
+	pallet_name: &[u8],
+	claim: &[u8],
+) -> Result<VerifiedClaim<AccountId, BlockNumber, Moment>, Error>
+where
+	H: HeaderT,
+	AccountId: Decode,
+	BlockNumber: Decode,
+	Moment: Decode,
+{
+	let db = proof.into_memory_db::<H::Hashing>();
+	let read = |storage_name: &[u8]| {
+		let key = storage_map_key(pallet_name, storage_name, claim);
+		read_trie_value::<Layout<H::Hashing>, _>(&db, header.state_root(), &key)
+			.map_err(|_| Error::InvalidProof)
+	};
+
+	let (owner, block_number) = read(PROOFS_STORAGE)?
+		.ok_or(Error::ClaimNotFound)
+		.and_then(|value| Decode::decode(&mut &value[..]).map_err(|_| Error::Decode))?;
+	// 旧存证可能没有时间戳,但证明必须能证实这一点,缺少节点的证明不能当作没有时间戳
+	let timestamp = match read(TIMESTAMPS_STORAGE).map_err(|_| Error::InvalidTimestampProof)? {
+		Some(value) => Some(Decode::decode(&mut &value[..]).map_err(|_| Error::Decode)?),
+		None => None,
+	};
+
+	Ok(VerifiedClaim { owner, block_number, timestamp })
+}
+
+/// 校验节点导出的证明包
+pub fn verify_bundle<H, AccountId, BlockNumber, Moment>(
+	bundle: &ProofBundle<H>,
+) -> Result<VerifiedClaim<AccountId, BlockNumber, Moment>, Error>
+where
+	H: HeaderT,
+	AccountId: Decode,
+	BlockNumber: Decode,
+	Moment: Decode,
+{
+	let proof = StorageProof::new(bundle.proof.clone());
+	verify_claim(&bundle.header, proof, &bundle.pallet_name, &bundle.claim)
+}
@@ -0,0 +1,117 @@
+use super::*;
+use sp_runtime::{generic, traits::BlakeTwo256};
+use sp_state_machine::{prove_read, InMemoryBackend};
+use std::collections::BTreeMap;
+
+type Header = generic::Header<u32, BlakeTwo256>;
+
+const PALLET: &[u8] = b"PoeModule";
+
+fn backend_with_claim(claim: &[u8]) -> InMemoryBackend<BlakeTwo256> {
+	let mut storage = BTreeMap::new();
+	storage.insert(storage_map_key(PALLET, PROOFS_STORAGE, claim), (7u64, 3u32).encode());
+	storage.insert(storage_map_key(PALLET, TIMESTAMPS_STORAGE, claim), 18_000u64.encode());
+	storage.insert(b"unrelated".to_vec(), vec![1]);
+	storage.into()
+}
+
+fn header_for(backend: &InMemoryBackend<BlakeTwo256>) -> Header {
+	Header::new(
+		5,
+		Default::default(),
+		*backend.root(),
+		Default::default(),
+		Default::default(),
+	)
+}
+
+fn prove(backend: InMemoryBackend<BlakeTwo256>, claim: &[u8]) -> StorageProof {
+	let keys = [
+		storage_map_key(PALLET, PROOFS_STORAGE, claim),
+		storage_map_key(PALLET, TIMESTAMPS_STORAGE, claim),
+	];
+	prove_read(backend, &keys).unwrap()
+}
+
+#[test]
+fn verify_claim_works() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim);
+	let header = header_for(&backend);
+	let proof = prove(backend, &claim);
+
+	assert_eq!(
+		verify_claim::<_, u64, u32, u64>(&header, proof, PALLET, &claim),
+		Ok(VerifiedClaim { owner: 7, block_number: 3, timestamp: Some(18_000) })
+	);
+}
+
+#[test]
+fn verify_bundle_works() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim);
+	let header = header_for(&backend);
+	let proof = prove(backend, &claim);
+	let bundle = ProofBundle {
+		header,
+		pallet_name: PALLET.to_vec(),
+		claim: claim.clone(),
+		proof: proof.iter_nodes().collect(),
+	};
+
+	let decoded = ProofBundle::<Header>::decode(&mut &bundle.encode()[..]).unwrap();
+	assert_eq!(verify_bundle::<_, u64, u32, u64>(&decoded).map(|c| c.owner), Ok(7));
+}
+
+#[test]
+fn verify_claim_failed_with_wrong_state_root() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim);
+	let mut header = header_for(&backend);
+	let proof = prove(backend, &claim);
+	header.state_root = Default::default();
+
+	assert_eq!(
+		verify_claim::<_, u64, u32, u64>(&header, proof, PALLET, &claim),
+		Err(Error::InvalidProof)
+	);
+}
+
+#[test]
+fn verify_claim_failed_for_other_claim() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim);
+	let header = header_for(&backend);
+	let proof = prove(backend, &claim);
+
+	assert!(verify_claim::<_, u64, u32, u64>(&header, proof, PALLET, &[2, 1]).is_err());
+}
+
+#[test]
+fn verify_claim_failed_without_timestamp_nodes() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim);
+	let header = header_for(&backend);
+	let proof = prove_read(backend, &[storage_map_key(PALLET, PROOFS_STORAGE, &claim)]).unwrap();
+
+	assert_eq!(
+		verify_claim::<_, u64, u32, u64>(&header, proof, PALLET, &claim),
+		Err(Error::InvalidTimestampProof)
+	);
+}
+
+#[test]
+fn verify_claim_works_without_timestamp() {
+	let claim = vec![0, 1];
+	let mut storage = BTreeMap::new();
+	storage.insert(storage_map_key(PALLET, PROOFS_STORAGE, &claim), (7u64, 3u32).encode());
+	storage.insert(b"unrelated".to_vec(), vec![1]);
+	let backend: InMemoryBackend<BlakeTwo256> = storage.into();
+	let header = header_for(&backend);
+	let proof = prove(backend, &claim);
+
+	assert_eq!(
+		verify_claim::<_, u64, u32, u64>(&header, proof, PALLET, &claim).map(|c| c.timestamp),
+		Ok(None)
+	);
+}
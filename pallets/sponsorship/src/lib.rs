@@ -0,0 +1,402 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! 交易赞助模块
+//!
+//! 赞助者登记预算并邀请用户,用户接受邀请后,其发起的白名单调用(如创建kitty、创建存证)由赞助者支付交易费用。
+//! 接受邀请的交易本身由发出邀请的赞助者支付;余额低于 `Endowment` 的用户接受邀请时,
+//! 赞助者从预算中为其补足余额,用于支付创建kitty的质押等押金,因此没有代币的新用户也能直接开始游戏。
+//! 用户可以随时离开赞助者;当前赞助者已退出时,用户可以接受其他赞助者的邀请。
+//! 费用通过 `ChargeSponsoredTransactionPayment` 扣除,它替代runtime `SignedExtra` 中的
+//! `ChargeTransactionPayment`:满足条件时向赞助者收费,否则仍由签名者自己支付。
+//! 赞助要求小费为零、赞助者预算足够,且用户在当前周期内的受赞助次数没有超过上限。
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::weights::{DispatchInfo, PostDispatchInfo};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, Saturating, SignedExtension, Zero},
+	transaction_validity::{TransactionValidity, TransactionValidityError},
+	DispatchResult, FixedPointOperand,
+};
+
+/// 交易费用的余额类型
+pub type BalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<
+	T,
+>>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::BalanceOf;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Contains, Currency, ExistenceRequirement, IsSubType},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Saturating, Zero};
+
+	/// 配置接口
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
+		/// 事件
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// 用于识别 `accept_sponsor` 调用
+		type Call: IsSubType<Call<Self>> + IsType<<Self as frame_system::Config>::Call>;
+		/// 可以被赞助的调用
+		type SponsoredCalls: Contains<<Self as frame_system::Config>::Call>;
+		/// 赞助者为新用户补足余额使用的货币
+		type Currency: Currency<Self::AccountId, Balance = BalanceOf<Self>>;
+		/// 用户接受邀请时赞助者为其补足到的余额,应足够支付创建kitty等调用的押金
+		#[pallet::constant]
+		type Endowment: Get<BalanceOf<Self>>;
+		/// 限速周期的区块数
+		#[pallet::constant]
+		type RateLimitPeriod: Get<Self::BlockNumber>;
+		/// 每个用户每个周期最多受赞助的交易数
+		#[pallet::constant]
+		type MaxSponsoredPerPeriod: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// 赞助者剩余的预算
+	#[pallet::storage]
+	#[pallet::getter(fn budget_of)]
+	pub type Budgets<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, OptionQuery>;
+
+	/// 用户 => 赞助者
+	#[pallet::storage]
+	#[pallet::getter(fn sponsor_of)]
+	pub type Sponsorships<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+	/// 等待用户接受的赞助邀请,(用户, 赞助者) => ()
+	#[pallet::storage]
+	pub type Offers<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// 用户当前周期的起始区块及已受赞助的交易数
+	#[pallet::storage]
+	pub type Usage<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// 设置了赞助预算,赞助者及预算
+		BudgetSet(T::AccountId, BalanceOf<T>),
+		/// 赞助者退出
+		SponsorRemoved(T::AccountId),
+		/// 邀请了用户,赞助者及用户
+		SponsorshipOffered(T::AccountId, T::AccountId),
+		/// 撤回了尚未被接受的邀请,赞助者及用户
+		OfferWithdrawn(T::AccountId, T::AccountId),
+		/// 用户接受了邀请,赞助者及用户
+		UserAdded(T::AccountId, T::AccountId),
+		/// 赞助者为新用户补足了余额,赞助者、用户及金额
+		Endowed(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// 用户离开了赞助者,赞助者及用户
+		UserLeft(T::AccountId, T::AccountId),
+		/// 移除了受赞助的用户,赞助者及用户
+		UserRemoved(T::AccountId, T::AccountId),
+		/// 赞助了一笔交易,赞助者、用户及预扣的费用
+		Sponsored(T::AccountId, T::AccountId, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// 不是赞助者
+		NotSponsor,
+		/// 用户已有赞助者
+		AlreadySponsored,
+		/// 不是该赞助者的用户
+		NotSponsoredUser,
+		/// 没有该赞助者的邀请
+		NoOffer,
+		/// 已向该用户发出邀请
+		AlreadyOffered,
+		/// 赞助者的预算不足以为用户补足余额
+		InsufficientBudget,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// 登记为赞助者或调整预算,费用从赞助者的余额中支付,预算限制赞助的总额
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_budget(origin: OriginFor<T>, budget: BalanceOf<T>) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			Budgets::<T>::insert(&sponsor, budget);
+			Self::deposit_event(Event::BudgetSet(sponsor, budget));
+			Ok(())
+		}
+
+		/// 退出赞助,其用户之后自己支付费用
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_sponsor(origin: OriginFor<T>) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			ensure!(Budgets::<T>::contains_key(&sponsor), Error::<T>::NotSponsor);
+			Budgets::<T>::remove(&sponsor);
+			Self::deposit_event(Event::SponsorRemoved(sponsor));
+			Ok(())
+		}
+
+		/// 邀请用户,用户通过 `accept_sponsor` 接受后才受赞助
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn add_user(origin: OriginFor<T>, user: T::AccountId) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			ensure!(Budgets::<T>::contains_key(&sponsor), Error::<T>::NotSponsor);
+			ensure!(Sponsorships::<T>::get(&user) != Some(sponsor.clone()), Error::<T>::AlreadySponsored);
+			ensure!(!Offers::<T>::contains_key(&user, &sponsor), Error::<T>::AlreadyOffered);
+			Offers::<T>::insert(&user, &sponsor, ());
+			Self::deposit_event(Event::SponsorshipOffered(sponsor, user));
+			Ok(())
+		}
+
+		/// 移除受赞助的用户,或撤回尚未被接受的邀请
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn remove_user(origin: OriginFor<T>, user: T::AccountId) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			if Offers::<T>::take(&user, &sponsor).is_some() {
+				Self::deposit_event(Event::OfferWithdrawn(sponsor, user));
+				return Ok(())
+			}
+			ensure!(Sponsorships::<T>::get(&user) == Some(sponsor.clone()), Error::<T>::NotSponsoredUser);
+			Sponsorships::<T>::remove(&user);
+			Usage::<T>::remove(&user);
+			Self::deposit_event(Event::UserRemoved(sponsor, user));
+			Ok(())
+		}
+
+		/// 接受赞助者的邀请;已有赞助者时只有在其已退出(没有预算)时才能更换
+		/// 用户余额低于 `Endowment` 时由赞助者从预算中补足
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 5))]
+		pub fn accept_sponsor(origin: OriginFor<T>, sponsor: T::AccountId) -> DispatchResult {
+			let user = ensure_signed(origin)?;
+			ensure!(Offers::<T>::contains_key(&user, &sponsor), Error::<T>::NoOffer);
+			let budget = Budgets::<T>::get(&sponsor).ok_or(Error::<T>::NotSponsor)?;
+			if let Some(current) = Sponsorships::<T>::get(&user) {
+				ensure!(!Budgets::<T>::contains_key(&current), Error::<T>::AlreadySponsored);
+			}
+
+			let endowment = T::Endowment::get().saturating_sub(T::Currency::free_balance(&user));
+			if !endowment.is_zero() {
+				ensure!(budget >= endowment, Error::<T>::InsufficientBudget);
+				T::Currency::transfer(&sponsor, &user, endowment, ExistenceRequirement::KeepAlive)?;
+				Budgets::<T>::insert(&sponsor, budget.saturating_sub(endowment));
+				Self::deposit_event(Event::Endowed(sponsor.clone(), user.clone(), endowment));
+			}
+
+			Offers::<T>::remove(&user, &sponsor);
+			Sponsorships::<T>::insert(&user, &sponsor);
+			Self::deposit_event(Event::UserAdded(sponsor, user));
+			Ok(())
+		}
+
+		/// 离开当前的赞助者,之后自己支付费用
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn leave_sponsor(origin: OriginFor<T>) -> DispatchResult {
+			let user = ensure_signed(origin)?;
+			let sponsor = Sponsorships::<T>::take(&user).ok_or(Error::<T>::NotSponsoredUser)?;
+			Usage::<T>::remove(&user);
+			Self::deposit_event(Event::UserLeft(sponsor, user));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 为用户的调用支付费用 `fee` 的赞助者,不满足赞助条件时返回 `None`
+		/// 接受邀请的调用由发出邀请的赞助者支付,其他调用须在白名单中并由用户当前的赞助者支付
+		pub fn sponsor_for(
+			who: &T::AccountId,
+			call: &<T as frame_system::Config>::Call,
+			fee: BalanceOf<T>,
+		) -> Option<T::AccountId> {
+			let sponsor = match <T as Config>::Call::from_ref(call).is_sub_type() {
+				Some(Call::accept_sponsor { sponsor }) => {
+					if !Self::can_accept(who, sponsor) {
+						return None
+					}
+					sponsor.clone()
+				},
+				_ => {
+					if !T::SponsoredCalls::contains(call) {
+						return None
+					}
+					Sponsorships::<T>::get(who)?
+				},
+			};
+			if Budgets::<T>::get(&sponsor)? < fee {
+				return None
+			}
+			let (_, count) = Self::current_usage(who);
+			if count >= T::MaxSponsoredPerPeriod::get() {
+				return None
+			}
+			Some(sponsor)
+		}
+
+		/// 用户是否有该赞助者的邀请且可以接受(没有赞助者或当前赞助者已退出)
+		fn can_accept(who: &T::AccountId, sponsor: &T::AccountId) -> bool {
+			Offers::<T>::contains_key(who, sponsor) &&
+				Sponsorships::<T>::get(who).map_or(true, |current| !Budgets::<T>::contains_key(&current))
+		}
+
+		/// 用户当前周期的起始区块及受赞助次数,周期结束后重新计数
+		fn current_usage(who: &T::AccountId) -> (T::BlockNumber, u32) {
+			let now = frame_system::Pallet::<T>::block_number();
+			let (start, count) = Usage::<T>::get(who);
+			if now >= start.saturating_add(T::RateLimitPeriod::get()) {
+				(now, 0)
+			} else {
+				(start, count)
+			}
+		}
+
+		/// 记录一次赞助:扣减预算并累计用户的受赞助次数
+		pub(crate) fn note_sponsored(sponsor: &T::AccountId, who: &T::AccountId, fee: BalanceOf<T>) {
+			Budgets::<T>::mutate(sponsor, |budget| {
+				if let Some(budget) = budget {
+					*budget = budget.saturating_sub(fee);
+				}
+			});
+			let (start, count) = Self::current_usage(who);
+			Usage::<T>::insert(who, (start, count.saturating_add(1)));
+			Self::deposit_event(Event::Sponsored(sponsor.clone(), who.clone(), fee));
+		}
+
+		/// 把多预扣的费用退回预算
+		pub(crate) fn refund_budget(sponsor: &T::AccountId, refund: BalanceOf<T>) {
+			if refund.is_zero() {
+				return
+			}
+			Budgets::<T>::mutate(sponsor, |budget| {
+				if let Some(budget) = budget {
+					*budget = budget.saturating_add(refund);
+				}
+			});
+		}
+	}
+}
+
+/// 支持赞助的交易费用扩展,替代 `ChargeTransactionPayment`
+///
+/// 编码与 `ChargeTransactionPayment` 相同,只包含小费;带小费的交易不会被赞助。
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsoredTransactionPayment<T: Config>(#[codec(compact)] BalanceOf<T>);
+
+impl<T: Config> ChargeSponsoredTransactionPayment<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	<T as frame_system::Config>::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	/// 按小费构造
+	pub fn from(tip: BalanceOf<T>) -> Self {
+		Self(tip)
+	}
+
+	/// 为这笔交易付费的赞助者及预扣的费用
+	fn sponsorship(
+		&self,
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::Call,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		len: usize,
+	) -> Option<(T::AccountId, BalanceOf<T>)> {
+		if !self.0.is_zero() {
+			return None
+		}
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.0);
+		Pallet::<T>::sponsor_for(who, call, fee).map(|sponsor| (sponsor, fee))
+	}
+}
+
+impl<T: Config> sp_std::fmt::Debug for ChargeSponsoredTransactionPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeSponsoredTransactionPayment<{:?}>", self.0)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeSponsoredTransactionPayment<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	<T as frame_system::Config>::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "ChargeSponsoredTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	/// 原费用扩展的 `Pre`,以及赞助者和预扣的费用
+	type Pre = (
+		<ChargeTransactionPayment<T> as SignedExtension>::Pre,
+		Option<(T::AccountId, BalanceOf<T>)>,
+	);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let payer = self.sponsorship(who, call, info, len).map_or_else(|| who.clone(), |(sponsor, _)| sponsor);
+		ChargeTransactionPayment::<T>::from(self.0).validate(&payer, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let sponsorship = self.sponsorship(who, call, info, len);
+		let payer = sponsorship.as_ref().map_or_else(|| who.clone(), |(sponsor, _)| sponsor.clone());
+		let pre = ChargeTransactionPayment::<T>::from(self.0).pre_dispatch(&payer, call, info, len)?;
+		if let Some((sponsor, fee)) = &sponsorship {
+			Pallet::<T>::note_sponsored(sponsor, who, *fee);
+		}
+		Ok((pre, sponsorship))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (pre, sponsorship) = pre;
+		ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)?;
+		if let Some((sponsor, fee)) = sponsorship {
+			// 赞助的交易没有小费,实际费用按执行后的权重重新计算
+			let actual_fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+				len as u32,
+				info,
+				post_info,
+				Zero::zero(),
+			);
+			Pallet::<T>::refund_budget(&sponsor, fee.saturating_sub(actual_fee));
+		}
+		Ok(())
+	}
+}
+
@@ -0,0 +1,237 @@
+use super::*;
+use crate::mock::{new_test_ext, Balances, Call, Event as MockEvent, Origin, Sponsorship, System, Test};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchClass, DispatchInfo, Pays, PostDispatchInfo},
+};
+
+/// 权重 10、长度 10 的交易费用为 20
+const INFO: DispatchInfo = DispatchInfo { weight: 10, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+const LEN: usize = 10;
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn charge(
+	who: u64,
+	call: &Call,
+	tip: u64,
+) -> <ChargeSponsoredTransactionPayment<Test> as SignedExtension>::Pre {
+	ChargeSponsoredTransactionPayment::<Test>::from(tip)
+		.pre_dispatch(&who, call, &INFO, LEN)
+		.unwrap()
+}
+
+/// 赞助者邀请用户,用户接受邀请
+fn sponsor(sponsor: u64, user: u64, budget: u64) {
+	assert_ok!(Sponsorship::set_budget(Origin::signed(sponsor), budget));
+	assert_ok!(Sponsorship::add_user(Origin::signed(sponsor), user));
+	assert_ok!(Sponsorship::accept_sponsor(Origin::signed(user), sponsor));
+}
+
+fn sponsor_user_2() {
+	sponsor(1, 2, 100);
+}
+
+#[test]
+fn test_register_sponsor_and_users() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Sponsorship::add_user(Origin::signed(1), 2), Error::<Test>::NotSponsor);
+		sponsor_user_2();
+		System::assert_last_event(MockEvent::Sponsorship(Event::UserAdded(1, 2)));
+		assert_eq!(Sponsorship::budget_of(1), Some(100));
+		assert_eq!(Sponsorship::sponsor_of(2), Some(1));
+
+		assert_noop!(Sponsorship::add_user(Origin::signed(1), 2), Error::<Test>::AlreadySponsored);
+		assert_ok!(Sponsorship::set_budget(Origin::signed(3), 10));
+		assert_noop!(Sponsorship::remove_user(Origin::signed(3), 2), Error::<Test>::NotSponsoredUser);
+
+		assert_ok!(Sponsorship::remove_user(Origin::signed(1), 2));
+		System::assert_last_event(MockEvent::Sponsorship(Event::UserRemoved(1, 2)));
+		assert_eq!(Sponsorship::sponsor_of(2), None);
+
+		assert_ok!(Sponsorship::remove_sponsor(Origin::signed(1)));
+		assert_noop!(Sponsorship::remove_sponsor(Origin::signed(1)), Error::<Test>::NotSponsor);
+	});
+}
+
+#[test]
+fn test_user_must_accept_offer() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_budget(Origin::signed(1), 100));
+		assert_ok!(Sponsorship::add_user(Origin::signed(1), 2));
+		System::assert_last_event(MockEvent::Sponsorship(Event::SponsorshipOffered(1, 2)));
+		assert_noop!(Sponsorship::add_user(Origin::signed(1), 2), Error::<Test>::AlreadyOffered);
+		// 未接受邀请前不受赞助
+		assert_eq!(Sponsorship::sponsor_of(2), None);
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(2), 80);
+
+		assert_noop!(Sponsorship::accept_sponsor(Origin::signed(2), 3), Error::<Test>::NoOffer);
+		assert_ok!(Sponsorship::accept_sponsor(Origin::signed(2), 1));
+		System::assert_last_event(MockEvent::Sponsorship(Event::UserAdded(1, 2)));
+		assert_eq!(Sponsorship::sponsor_of(2), Some(1));
+		assert_noop!(Sponsorship::accept_sponsor(Origin::signed(2), 1), Error::<Test>::NoOffer);
+
+		// 撤回未接受的邀请
+		assert_ok!(Sponsorship::add_user(Origin::signed(1), 3));
+		assert_ok!(Sponsorship::remove_user(Origin::signed(1), 3));
+		System::assert_last_event(MockEvent::Sponsorship(Event::OfferWithdrawn(1, 3)));
+		assert_noop!(Sponsorship::accept_sponsor(Origin::signed(3), 1), Error::<Test>::NoOffer);
+	});
+}
+
+#[test]
+fn test_sponsor_pays_accept_and_endows_new_user() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_budget(Origin::signed(1), 100));
+		assert_ok!(Sponsorship::add_user(Origin::signed(1), 4));
+		// 没有余额的用户接受邀请的交易由赞助者支付
+		let accept = Call::Sponsorship(crate::Call::accept_sponsor { sponsor: 1 });
+		charge(4, &accept, 0);
+		assert_eq!(Balances::free_balance(1), 980);
+		assert_eq!(Sponsorship::budget_of(1), Some(80));
+
+		assert_ok!(Sponsorship::accept_sponsor(Origin::signed(4), 1));
+		System::assert_has_event(MockEvent::Sponsorship(Event::Endowed(1, 4, 50)));
+		assert_eq!(Balances::free_balance(4), 50);
+		assert_eq!(Balances::free_balance(1), 930);
+		assert_eq!(Sponsorship::budget_of(1), Some(30));
+
+		// 没有邀请时不赞助
+		let ext = ChargeSponsoredTransactionPayment::<Test>::from(0);
+		assert!(ext.validate(&5, &accept, &INFO, LEN).is_err());
+		// 余额已足够的用户不再补足
+		assert_ok!(Sponsorship::add_user(Origin::signed(1), 2));
+		assert_ok!(Sponsorship::accept_sponsor(Origin::signed(2), 1));
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Sponsorship::budget_of(1), Some(30));
+	});
+}
+
+#[test]
+fn test_endowment_limited_by_budget() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_budget(Origin::signed(1), 40));
+		assert_ok!(Sponsorship::add_user(Origin::signed(1), 4));
+		assert_noop!(
+			Sponsorship::accept_sponsor(Origin::signed(4), 1),
+			Error::<Test>::InsufficientBudget
+		);
+	});
+}
+
+#[test]
+fn test_user_leaves_or_replaces_sponsor() {
+	new_test_ext().execute_with(|| {
+		sponsor_user_2();
+		assert_ok!(Sponsorship::set_budget(Origin::signed(3), 100));
+		assert_ok!(Sponsorship::add_user(Origin::signed(3), 2));
+		// 当前赞助者仍有预算时不能更换
+		assert_noop!(Sponsorship::accept_sponsor(Origin::signed(2), 3), Error::<Test>::AlreadySponsored);
+
+		// 当前赞助者退出后可以更换
+		assert_ok!(Sponsorship::remove_sponsor(Origin::signed(1)));
+		assert_ok!(Sponsorship::accept_sponsor(Origin::signed(2), 3));
+		assert_eq!(Sponsorship::sponsor_of(2), Some(3));
+
+		assert_ok!(Sponsorship::leave_sponsor(Origin::signed(2)));
+		System::assert_last_event(MockEvent::Sponsorship(Event::UserLeft(3, 2)));
+		assert_eq!(Sponsorship::sponsor_of(2), None);
+		assert_noop!(Sponsorship::leave_sponsor(Origin::signed(2)), Error::<Test>::NotSponsoredUser);
+	});
+}
+
+#[test]
+fn test_sponsor_pays_whitelisted_calls() {
+	new_test_ext().execute_with(|| {
+		sponsor_user_2();
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(1), 980);
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Sponsorship::budget_of(1), Some(80));
+		System::assert_last_event(MockEvent::Sponsorship(Event::Sponsored(1, 2, 20)));
+	});
+}
+
+#[test]
+fn test_user_pays_otherwise() {
+	new_test_ext().execute_with(|| {
+		sponsor_user_2();
+		// 不在白名单的调用
+		charge(2, &Call::System(frame_system::Call::remark_with_event { remark: vec![] }), 0);
+		assert_eq!(Balances::free_balance(2), 80);
+		// 带小费的交易
+		charge(2, &remark(), 5);
+		assert_eq!(Balances::free_balance(2), 55);
+		// 没有赞助者的用户
+		charge(3, &remark(), 0);
+		assert_eq!(Balances::free_balance(3), 80);
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Sponsorship::budget_of(1), Some(100));
+	});
+}
+
+#[test]
+fn test_budget_limit() {
+	new_test_ext().execute_with(|| {
+		sponsor(1, 2, 30);
+		charge(2, &remark(), 0);
+		assert_eq!(Sponsorship::budget_of(1), Some(10));
+		// 预算不足,由用户自己支付
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(1), 980);
+		assert_eq!(Balances::free_balance(2), 80);
+	});
+}
+
+#[test]
+fn test_rate_limit() {
+	new_test_ext().execute_with(|| {
+		sponsor_user_2();
+		charge(2, &remark(), 0);
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(1), 960);
+		// 本周期的次数已用完
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(2), 80);
+
+		// 下一个周期重新计数
+		System::set_block_number(11);
+		charge(2, &remark(), 0);
+		assert_eq!(Balances::free_balance(1), 940);
+		assert_eq!(Balances::free_balance(2), 80);
+	});
+}
+
+#[test]
+fn test_refund_returns_to_sponsor() {
+	new_test_ext().execute_with(|| {
+		sponsor_user_2();
+		let pre = charge(2, &remark(), 0);
+		// 实际权重为 4,费用 14,多扣的 6 退回赞助者的余额及预算
+		let post_info = PostDispatchInfo { actual_weight: Some(4), pays_fee: Pays::Yes };
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			pre,
+			&INFO,
+			&post_info,
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Balances::free_balance(1), 986);
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Sponsorship::budget_of(1), Some(86));
+	});
+}
+
+#[test]
+fn test_validate_checks_sponsor_balance() {
+	new_test_ext().execute_with(|| {
+		// 用户 4 只有赞助者补足的余额,交易费用由赞助者支付
+		sponsor(1, 4, 100);
+		let ext = ChargeSponsoredTransactionPayment::<Test>::from(0);
+		assert!(ext.validate(&4, &remark(), &INFO, LEN).is_ok());
+		assert!(ext.validate(&5, &remark(), &INFO, LEN).is_err());
+	});
+}
@@ -5,15 +5,20 @@ use super::*;
 #[allow(unused)]
 use crate::Pallet as Template;
 use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_support::traits::{Currency, Get};
 use frame_system::RawOrigin;
+use sp_std::vec;
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	set {
+		let k in 1 .. T::MaxKeyLength::get();
+		let v in 0 .. T::MaxValueLength::get();
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::from(u32::MAX));
+		Template::<T>::create_namespace(RawOrigin::Signed(caller.clone()).into(), b"bench".to_vec())?;
+	}: _(RawOrigin::Signed(caller), b"bench".to_vec(), vec![0; k as usize], vec![1; v as usize])
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(Template::<T>::get(b"bench", &vec![0; k as usize]), Some(vec![1; v as usize]));
 	}
 
 	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
@@ -1,10 +1,16 @@
+//! A namespaced on-chain key/value registry for application configuration.
+//!
+//! Any account can open a namespace and write bounded keys and values into it. Every namespace
+//! and entry holds a deposit from its owner, returned when it is removed. Namespaces without an
+//! owner (such as the `default` namespace created by the migration from `Something`) are
+//! managed by `ForceOrigin`.
+
 #![cfg_attr(not(feature = "std"), no_std)]
 
-/// Edit this file to define custom logic or remove it if it is not needed.
-/// Learn more about FRAME and the core library of Substrate FRAME pallets:
-/// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
+pub mod migrations;
+
 #[cfg(test)]
 mod mock;
 
@@ -16,45 +22,142 @@ mod benchmarking;
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use codec::{Decode, Encode};
+	use frame_support::{
+		dispatch::DispatchResult,
+		pallet_prelude::*,
+		sp_runtime::traits::{Saturating, Zero},
+		traits::{Currency, ReservableCurrency, StorageVersion},
+	};
 	use frame_system::pallet_prelude::*;
+	use scale_info::TypeInfo;
+	use sp_std::vec::Vec;
+
+	/// The current storage version.
+	pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	/// Names of namespaces and keys.
+	pub type KeyOf<T> = BoundedVec<u8, <T as Config>::MaxKeyLength>;
+	/// Stored values.
+	pub type ValueOf<T> = BoundedVec<u8, <T as Config>::MaxValueLength>;
+
+	/// A namespace of entries.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct Namespace<AccountId, Balance> {
+		/// The account allowed to write into the namespace. `None` if it is managed by
+		/// `ForceOrigin`.
+		pub owner: Option<AccountId>,
+		/// The deposit reserved from the owner for the namespace itself.
+		pub deposit: Balance,
+		/// The number of entries in the namespace.
+		pub entries: u32,
+	}
+
+	/// A value stored in a namespace.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Entry<T: Config> {
+		/// The stored bytes.
+		pub value: ValueOf<T>,
+		/// The deposit reserved from the namespace owner for this entry.
+		pub deposit: BalanceOf<T>,
+	}
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency deposits are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The origin that manages namespaces without an owner.
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The deposit reserved for opening a namespace.
+		#[pallet::constant]
+		type NamespaceDeposit: Get<BalanceOf<Self>>;
+
+		/// The base deposit reserved for each entry.
+		#[pallet::constant]
+		type DepositBase: Get<BalanceOf<Self>>;
+
+		/// The additional deposit reserved per byte of key and value.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+
+		/// The maximum length of a namespace name or key.
+		#[pallet::constant]
+		type MaxKeyLength: Get<u32>;
+
+		/// The maximum length of a value.
+		#[pallet::constant]
+		type MaxValueLength: Get<u32>;
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// The pallet's runtime storage items.
 	// https://docs.substrate.io/v3/runtime/storage
+	/// Namespaces by name.
+	#[pallet::storage]
+	#[pallet::getter(fn namespace)]
+	pub type Namespaces<T: Config> =
+		StorageMap<_, Blake2_128Concat, KeyOf<T>, Namespace<T::AccountId, BalanceOf<T>>>;
+
+	/// Entries by namespace and key.
 	#[pallet::storage]
-	#[pallet::getter(fn something)]
-	// Learn more about declaring storage items:
-	// https://docs.substrate.io/v3/runtime/storage#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
+	#[pallet::getter(fn entry)]
+	pub type Entries<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, KeyOf<T>, Blake2_128Concat, KeyOf<T>, Entry<T>>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/v3/runtime/events-and-errors
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Event documentation should end with an array that provides descriptive names for event
-		/// parameters. [something, who]
-		SomethingStored(u32, T::AccountId),
+		/// A namespace was opened. [namespace, owner]
+		NamespaceCreated(KeyOf<T>, Option<T::AccountId>),
+		/// An empty namespace was removed. [namespace]
+		NamespaceRemoved(KeyOf<T>),
+		/// A value was written. [namespace, key]
+		EntrySet(KeyOf<T>, KeyOf<T>),
+		/// A value was removed. [namespace, key]
+		EntryRemoved(KeyOf<T>, KeyOf<T>),
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		/// Error names should be descriptive.
-		NoneValue,
-		/// Errors should have helpful documentation associated with them.
-		StorageOverflow,
+		/// The namespace name or key is longer than `MaxKeyLength`.
+		KeyTooLong,
+		/// The value is longer than `MaxValueLength`.
+		ValueTooLong,
+		/// The namespace already exists.
+		NamespaceExists,
+		/// The namespace does not exist.
+		UnknownNamespace,
+		/// The key does not exist in the namespace.
+		UnknownKey,
+		/// The sender does not own the namespace.
+		NotOwner,
+		/// The namespace still has entries.
+		NamespaceNotEmpty,
+		/// The owner cannot afford the deposit.
+		InsufficientDeposit,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::migrate_to_v1::<T>()
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -62,41 +165,157 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
-			// Check that the extrinsic was signed and get the signer.
-			// This function will return an error if the extrinsic is not signed.
-			// https://docs.substrate.io/v3/runtime/origins
+		/// Open a namespace owned by the sender, reserving `NamespaceDeposit`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn create_namespace(origin: OriginFor<T>, namespace: Vec<u8>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			let namespace = Self::bound_key(namespace)?;
+			ensure!(!Namespaces::<T>::contains_key(&namespace), Error::<T>::NamespaceExists);
+
+			let deposit = T::NamespaceDeposit::get();
+			T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientDeposit)?;
+			Namespaces::<T>::insert(
+				&namespace,
+				Namespace { owner: Some(who.clone()), deposit, entries: 0 },
+			);
+			Self::deposit_event(Event::NamespaceCreated(namespace, Some(who)));
+			Ok(())
+		}
 
-			// Update storage.
-			<Something<T>>::put(something);
+		/// Remove an empty namespace owned by the sender and return its deposit.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn remove_namespace(origin: OriginFor<T>, namespace: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let namespace = Self::bound_key(namespace)?;
+			let info = Self::owned_namespace(&namespace, Some(&who))?;
+			ensure!(info.entries == 0, Error::<T>::NamespaceNotEmpty);
 
-			// Emit an event.
-			Self::deposit_event(Event::SomethingStored(something, who));
-			// Return a successful DispatchResultWithPostInfo
+			T::Currency::unreserve(&who, info.deposit);
+			Namespaces::<T>::remove(&namespace);
+			Self::deposit_event(Event::NamespaceRemoved(namespace));
 			Ok(())
 		}
 
-		/// An example dispatchable that may throw a custom error.
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
-		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			// Read a value from storage.
-			match <Something<T>>::get() {
-				// Return an error if the value has not been set.
-				None => Err(Error::<T>::NoneValue)?,
-				Some(old) => {
-					// Increment the value read from storage; will error in the event of overflow.
-					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
-					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
-					Ok(())
+		/// Write a value into a namespace owned by the sender.
+		///
+		/// The entry deposit is `DepositBase` plus `DepositPerByte` for every byte of key and
+		/// value. Overwriting an entry reserves or returns the difference.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn set(origin: OriginFor<T>, namespace: Vec<u8>, key: Vec<u8>, value: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_set(Some(who), namespace, key, value)
+		}
+
+		/// Remove a value from a namespace owned by the sender and return its deposit.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn remove(origin: OriginFor<T>, namespace: Vec<u8>, key: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_remove(Some(who), namespace, key)
+		}
+
+		/// Write a value into a namespace without an owner, creating the namespace if needed.
+		/// No deposit is taken.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn force_set(
+			origin: OriginFor<T>,
+			namespace: Vec<u8>,
+			key: Vec<u8>,
+			value: Vec<u8>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			let name = Self::bound_key(namespace.clone())?;
+			if !Namespaces::<T>::contains_key(&name) {
+				Self::insert_system_namespace(&name);
+			}
+			Self::do_set(None, namespace, key, value)
+		}
+
+		/// Remove a value from a namespace without an owner.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn force_remove(origin: OriginFor<T>, namespace: Vec<u8>, key: Vec<u8>) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			Self::do_remove(None, namespace, key)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Read a value.
+		pub fn get(namespace: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+			let namespace = KeyOf::<T>::try_from(namespace.to_vec()).ok()?;
+			let key = KeyOf::<T>::try_from(key.to_vec()).ok()?;
+			Self::entry(namespace, key).map(|entry| entry.value.into_inner())
+		}
+
+		/// The deposit for an entry with the given key and value lengths.
+		pub fn entry_deposit(key_len: usize, value_len: usize) -> BalanceOf<T> {
+			let bytes = BalanceOf::<T>::from((key_len + value_len) as u32);
+			T::DepositBase::get().saturating_add(T::DepositPerByte::get().saturating_mul(bytes))
+		}
+
+		/// Open a namespace without an owner.
+		pub(crate) fn insert_system_namespace(namespace: &KeyOf<T>) {
+			Namespaces::<T>::insert(namespace, Namespace { owner: None, deposit: Zero::zero(), entries: 0 });
+			Self::deposit_event(Event::NamespaceCreated(namespace.clone(), None));
+		}
+
+		fn do_set(owner: Option<T::AccountId>, namespace: Vec<u8>, key: Vec<u8>, value: Vec<u8>) -> DispatchResult {
+			let namespace = Self::bound_key(namespace)?;
+			let key = Self::bound_key(key)?;
+			let value: ValueOf<T> = value.try_into().map_err(|_| Error::<T>::ValueTooLong)?;
+			let mut info = Self::owned_namespace(&namespace, owner.as_ref())?;
+
+			let old = Self::entry(&namespace, &key);
+			let old_deposit = old.as_ref().map_or_else(Zero::zero, |entry| entry.deposit);
+			let deposit = match &owner {
+				Some(who) => {
+					let deposit = Self::entry_deposit(key.len(), value.len());
+					if deposit > old_deposit {
+						T::Currency::reserve(who, deposit - old_deposit)
+							.map_err(|_| Error::<T>::InsufficientDeposit)?;
+					} else {
+						T::Currency::unreserve(who, old_deposit - deposit);
+					}
+					deposit
 				},
+				None => Zero::zero(),
+			};
+
+			if old.is_none() {
+				info.entries = info.entries.saturating_add(1);
+				Namespaces::<T>::insert(&namespace, info);
 			}
+			Entries::<T>::insert(&namespace, &key, Entry { value, deposit });
+			Self::deposit_event(Event::EntrySet(namespace, key));
+			Ok(())
+		}
+
+		fn do_remove(owner: Option<T::AccountId>, namespace: Vec<u8>, key: Vec<u8>) -> DispatchResult {
+			let namespace = Self::bound_key(namespace)?;
+			let key = Self::bound_key(key)?;
+			let mut info = Self::owned_namespace(&namespace, owner.as_ref())?;
+			let entry = Entries::<T>::take(&namespace, &key).ok_or(Error::<T>::UnknownKey)?;
+
+			if let Some(who) = &owner {
+				T::Currency::unreserve(who, entry.deposit);
+			}
+			info.entries = info.entries.saturating_sub(1);
+			Namespaces::<T>::insert(&namespace, info);
+			Self::deposit_event(Event::EntryRemoved(namespace, key));
+			Ok(())
+		}
+
+		/// Fetch a namespace, checking that it belongs to `owner`.
+		fn owned_namespace(
+			namespace: &KeyOf<T>,
+			owner: Option<&T::AccountId>,
+		) -> Result<Namespace<T::AccountId, BalanceOf<T>>, Error<T>> {
+			let info = Self::namespace(namespace).ok_or(Error::<T>::UnknownNamespace)?;
+			ensure!(info.owner.as_ref() == owner, Error::<T>::NotOwner);
+			Ok(info)
+		}
+
+		fn bound_key(key: Vec<u8>) -> Result<KeyOf<T>, Error<T>> {
+			key.try_into().map_err(|_| Error::<T>::KeyTooLong)
 		}
 	}
 }
@@ -0,0 +1,39 @@
+//! Storage migrations.
+
+use crate::{Config, Entries, Entry, KeyOf, Namespace, Namespaces, Pallet};
+use frame_support::{
+	sp_runtime::traits::Zero,
+	storage::migration::take_storage_value,
+	traits::{Get, GetStorageVersion, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+};
+
+/// The namespace the old `Something` value is moved into.
+pub const DEFAULT_NAMESPACE: &[u8] = b"default";
+/// The key the old `Something` value is stored under, SCALE encoded.
+pub const SOMETHING_KEY: &[u8] = b"something";
+
+/// Move the single `u32` from the v0 `Something` storage item into the `default` namespace.
+pub fn migrate_to_v1<T: Config>() -> Weight {
+	if Pallet::<T>::on_chain_storage_version() >= 1 {
+		return 0
+	}
+
+	let mut weight = T::DbWeight::get().reads_writes(2, 2);
+	let old = take_storage_value::<u32>(Pallet::<T>::name().as_bytes(), b"Something", &[]);
+	if let Some(something) = old {
+		let namespace: KeyOf<T> =
+			DEFAULT_NAMESPACE.to_vec().try_into().expect("`MaxKeyLength` fits the default namespace");
+		let key: KeyOf<T> = SOMETHING_KEY.to_vec().try_into().expect("`MaxKeyLength` fits the key");
+		let value = codec::Encode::encode(&something)
+			.try_into()
+			.expect("`MaxValueLength` fits a u32");
+
+		Namespaces::<T>::insert(&namespace, Namespace { owner: None, deposit: Zero::zero(), entries: 1 });
+		Entries::<T>::insert(&namespace, &key, Entry::<T> { value, deposit: Zero::zero() });
+		weight = weight.saturating_add(T::DbWeight::get().writes(2));
+	}
+
+	StorageVersion::new(1).put::<Pallet<T>>();
+	weight
+}
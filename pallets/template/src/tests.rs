@@ -1,20 +1,149 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{migrations, mock::*, Error, Event, Namespace};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::migration::put_storage_value,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+
+fn key(bytes: &[u8]) -> crate::KeyOf<Test> {
+	bytes.to_vec().try_into().unwrap()
+}
+
+#[test]
+fn create_and_remove_namespace() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::create_namespace(Origin::signed(1), b"app".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_eq!(
+			TemplateModule::namespace(key(b"app")),
+			Some(Namespace { owner: Some(1), deposit: 100, entries: 0 })
+		);
+		System::assert_last_event(Event::NamespaceCreated(key(b"app"), Some(1)).into());
+
+		assert_noop!(
+			TemplateModule::create_namespace(Origin::signed(2), b"app".to_vec()),
+			Error::<Test>::NamespaceExists
+		);
+		assert_noop!(
+			TemplateModule::create_namespace(Origin::signed(1), vec![0; 17]),
+			Error::<Test>::KeyTooLong
+		);
+		assert_noop!(
+			TemplateModule::remove_namespace(Origin::signed(2), b"app".to_vec()),
+			Error::<Test>::NotOwner
+		);
+
+		assert_ok!(TemplateModule::remove_namespace(Origin::signed(1), b"app".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(TemplateModule::namespace(key(b"app")), None);
+	});
+}
+
+#[test]
+fn set_and_remove_entries() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::set(Origin::signed(1), b"app".to_vec(), b"k".to_vec(), b"v".to_vec()),
+			Error::<Test>::UnknownNamespace
+		);
+		assert_ok!(TemplateModule::create_namespace(Origin::signed(1), b"app".to_vec()));
+		assert_noop!(
+			TemplateModule::set(Origin::signed(2), b"app".to_vec(), b"k".to_vec(), b"v".to_vec()),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			TemplateModule::set(Origin::signed(1), b"app".to_vec(), b"k".to_vec(), vec![0; 33]),
+			Error::<Test>::ValueTooLong
+		);
+
+		// Deposit is the base plus one per byte of key and value.
+		assert_ok!(TemplateModule::set(Origin::signed(1), b"app".to_vec(), b"k".to_vec(), b"value".to_vec()));
+		assert_eq!(TemplateModule::get(b"app", b"k"), Some(b"value".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 100 + 16);
+		System::assert_last_event(Event::EntrySet(key(b"app"), key(b"k")).into());
+
+		// Overwriting only moves the difference.
+		assert_ok!(TemplateModule::set(Origin::signed(1), b"app".to_vec(), b"k".to_vec(), b"v".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 100 + 12);
+		assert_eq!(TemplateModule::namespace(key(b"app")).unwrap().entries, 1);
+
+		assert_noop!(
+			TemplateModule::remove_namespace(Origin::signed(1), b"app".to_vec()),
+			Error::<Test>::NamespaceNotEmpty
+		);
+		assert_noop!(
+			TemplateModule::remove(Origin::signed(1), b"app".to_vec(), b"x".to_vec()),
+			Error::<Test>::UnknownKey
+		);
+		assert_ok!(TemplateModule::remove(Origin::signed(1), b"app".to_vec(), b"k".to_vec()));
+		assert_eq!(TemplateModule::get(b"app", b"k"), None);
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_eq!(TemplateModule::namespace(key(b"app")).unwrap().entries, 0);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn set_requires_deposit() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		assert_ok!(TemplateModule::create_namespace(Origin::signed(2), b"app".to_vec()));
+		assert_noop!(
+			TemplateModule::set(Origin::signed(2), b"app".to_vec(), b"k".to_vec(), vec![0; 32]),
+			Error::<Test>::InsufficientDeposit
+		);
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn force_set_manages_ownerless_namespaces() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
+		assert_noop!(
+			TemplateModule::force_set(Origin::signed(1), b"sys".to_vec(), b"k".to_vec(), b"v".to_vec()),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_ok!(TemplateModule::force_set(Origin::root(), b"sys".to_vec(), b"k".to_vec(), b"v".to_vec()));
+		assert_eq!(
+			TemplateModule::namespace(key(b"sys")),
+			Some(Namespace { owner: None, deposit: 0, entries: 1 })
+		);
+		assert_eq!(TemplateModule::get(b"sys", b"k"), Some(b"v".to_vec()));
+
+		// Signed accounts cannot write into namespaces without an owner.
+		assert_noop!(
+			TemplateModule::set(Origin::signed(1), b"sys".to_vec(), b"k".to_vec(), b"x".to_vec()),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			TemplateModule::force_remove(Origin::root(), b"app".to_vec(), b"k".to_vec()),
+			Error::<Test>::UnknownNamespace
+		);
+		assert_ok!(TemplateModule::force_remove(Origin::root(), b"sys".to_vec(), b"k".to_vec()));
+		assert_eq!(TemplateModule::get(b"sys", b"k"), None);
+	});
+}
+
+#[test]
+fn migrates_something_into_default_namespace() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		put_storage_value(b"TemplateModule", b"Something", &[], 42u32);
+
+		TemplateModule::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(
+			TemplateModule::get(migrations::DEFAULT_NAMESPACE, migrations::SOMETHING_KEY),
+			Some(42u32.to_le_bytes().to_vec())
+		);
+		assert_eq!(
+			TemplateModule::namespace(key(migrations::DEFAULT_NAMESPACE)),
+			Some(Namespace { owner: None, deposit: 0, entries: 1 })
+		);
+		assert_eq!(
+			frame_support::storage::migration::get_storage_value::<u32>(b"TemplateModule", b"Something", &[]),
+			None
+		);
+
+		// Running again is a no-op.
+		assert_eq!(migrations::migrate_to_v1::<Test>(), 0);
 	});
 }
@@ -0,0 +1,175 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! 验证人集合管理模块
+//!
+//! 作为 `pallet_session` 的 `SessionManager`,由特权账户增删验证人,
+//! 变更在会话切换时提交给会话模块,并按会话模块的排队机制在下一个会话生效,无需硬分叉即可轮换节点运营方。
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_staking::SessionIndex;
+	use sp_std::vec::Vec;
+
+	/// 配置接口
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// 事件
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// 增删验证人的特权来源
+		type AddRemoveOrigin: EnsureOrigin<Self::Origin>;
+		/// 验证人数量下限
+		#[pallet::constant]
+		type MinValidators: Get<u32>;
+		/// 验证人数量上限
+		#[pallet::constant]
+		type MaxValidators: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// 下一次会话切换时使用的验证人
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub type Validators<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxValidators>, ValueQuery>;
+
+	/// 验证人集合在上次会话切换后是否有变更
+	#[pallet::storage]
+	#[pallet::getter(fn changed)]
+	pub type Changed<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// 创世验证人
+		pub initial_validators: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { initial_validators: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let validators: BoundedVec<_, _> =
+				self.initial_validators.clone().try_into().expect("too many genesis validators");
+			Validators::<T>::put(validators);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// 添加了验证人,下一个会话生效
+		ValidatorAdded(T::AccountId),
+		/// 移除了验证人,下一个会话生效
+		ValidatorRemoved(T::AccountId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// 已经是验证人
+		Duplicate,
+		/// 不是验证人
+		NotValidator,
+		/// 验证人数量不能少于下限
+		TooFewValidators,
+		/// 验证人数量不能超过上限
+		TooManyValidators,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// 添加验证人,验证人需要先通过 `session.set_keys` 设置会话密钥
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn add_validator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			Validators::<T>::try_mutate(|validators| {
+				ensure!(!validators.contains(&who), Error::<T>::Duplicate);
+				validators.try_push(who.clone()).map_err(|_| Error::<T>::TooManyValidators)
+			})?;
+			Changed::<T>::put(true);
+			Self::deposit_event(Event::ValidatorAdded(who));
+			Ok(())
+		}
+
+		/// 移除验证人
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn remove_validator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			Self::do_remove_validator(&who)?;
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 移除验证人,如举报成立的作恶验证人
+		pub fn do_remove_validator(who: &T::AccountId) -> Result<(), Error<T>> {
+			Validators::<T>::try_mutate(|validators| {
+				let index = validators.iter().position(|v| v == who).ok_or(Error::<T>::NotValidator)?;
+				ensure!(validators.len() as u32 > T::MinValidators::get(), Error::<T>::TooFewValidators);
+				validators.remove(index);
+				Ok::<_, Error<T>>(())
+			})?;
+			Changed::<T>::put(true);
+			Self::deposit_event(Event::ValidatorRemoved(who.clone()));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+		/// 验证人集合有变更时返回新的集合,否则沿用当前集合
+		fn new_session(_new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+			if Changed::<T>::take() {
+				Some(Self::validators().into_inner())
+			} else {
+				None
+			}
+		}
+
+		/// 没有配置创世验证人时使用会话模块创世配置中的密钥
+		fn new_session_genesis(_new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+			let validators = Self::validators().into_inner();
+			if validators.is_empty() {
+				None
+			} else {
+				Some(validators)
+			}
+		}
+
+		fn end_session(_end_index: SessionIndex) {}
+
+		fn start_session(_start_index: SessionIndex) {}
+	}
+
+	/// 完整身份即验证人账户,用于 `pallet_session::historical` 记录每个会话的验证人
+	impl<T: Config> pallet_session::historical::SessionManager<T::AccountId, T::AccountId> for Pallet<T> {
+		fn new_session(new_index: SessionIndex) -> Option<Vec<(T::AccountId, T::AccountId)>> {
+			<Self as pallet_session::SessionManager<_>>::new_session(new_index)
+				.map(|validators| validators.into_iter().map(|v| (v.clone(), v)).collect())
+		}
+
+		fn new_session_genesis(new_index: SessionIndex) -> Option<Vec<(T::AccountId, T::AccountId)>> {
+			<Self as pallet_session::SessionManager<_>>::new_session_genesis(new_index)
+				.map(|validators| validators.into_iter().map(|v| (v.clone(), v)).collect())
+		}
+
+		fn end_session(_end_index: SessionIndex) {}
+
+		fn start_session(_start_index: SessionIndex) {}
+	}
+}
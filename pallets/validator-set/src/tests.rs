@@ -0,0 +1,57 @@
+use super::*;
+use crate::mock::{new_test_ext, Event as MockEvent, Origin, System, Test, ValidatorSet};
+use frame_support::{assert_noop, assert_ok};
+use pallet_session::SessionManager;
+
+#[test]
+fn test_genesis_validators() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 2]);
+		assert_eq!(<ValidatorSet as SessionManager<_>>::new_session_genesis(0), Some(vec![1, 2]));
+		// 没有变更时沿用当前集合
+		assert_eq!(<ValidatorSet as SessionManager<_>>::new_session(1), None);
+	});
+}
+
+#[test]
+fn test_add_validator() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(Origin::signed(1), 3),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 1), Error::<Test>::Duplicate);
+
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		System::assert_last_event(MockEvent::ValidatorSet(Event::ValidatorAdded(3)));
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 4), Error::<Test>::TooManyValidators);
+
+		// 变更只在下一次会话切换时提交一次
+		assert_eq!(<ValidatorSet as SessionManager<_>>::new_session(1), Some(vec![1, 2, 3]));
+		assert_eq!(<ValidatorSet as SessionManager<_>>::new_session(2), None);
+	});
+}
+
+#[test]
+fn test_remove_validator() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 3), Error::<Test>::NotValidator);
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		System::assert_last_event(MockEvent::ValidatorSet(Event::ValidatorRemoved(1)));
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 2), Error::<Test>::TooFewValidators);
+
+		assert_eq!(
+			<ValidatorSet as pallet_session::historical::SessionManager<_, _>>::new_session(1),
+			Some(vec![(2, 2)])
+		);
+	});
+}
+
+#[test]
+fn test_empty_genesis_uses_session_keys() {
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| {
+		assert_eq!(<ValidatorSet as SessionManager<_>>::new_session_genesis(0), None);
+	});
+}
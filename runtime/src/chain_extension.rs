@@ -0,0 +1,80 @@
+//! A contracts chain extension giving ink! contracts access to kitties and proof-of-existence
+//! claims.
+//!
+//! Contracts select a function by its id; inputs and outputs are SCALE encoded:
+//!
+//! - `1`: takes a `KittyIndex`, returns `Option<([u8; 16], AccountId)>` with the kitty's DNA
+//!   and owner.
+//! - `2`: takes `(AccountId, KittyIndex)` and transfers a kitty owned by the calling contract to
+//!   the account. The contract must afford the kitty stake like any other owner, and the transfer
+//!   is rejected while `KittiesModule::transfer` is paused by maintenance mode.
+//! - `3`: takes the proof as a SCALE encoded `Vec<u8>` of at most the PoE claim length limit,
+//!   returns `Option<(AccountId, BlockNumber, Moment)>` with the claim's owner, block and time.
+//!
+//! Errors, including unknown function ids, abort the contract call.
+
+use crate::{AccountId, Call, Contains, KittiesModule, KittyIndex, PoeModule, Runtime, Schedule};
+use codec::{Compact, CompactLen, Encode};
+use frame_support::{traits::Get, weights::constants::RocksDbWeight};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+const QUERY_KITTY: u32 = 1;
+const TRANSFER_KITTY: u32 = 2;
+const QUERY_CLAIM: u32 = 3;
+
+/// Exposes `pallet_kitties` and `pallet_poe` to contracts.
+pub struct KittiesChainExtension;
+
+impl ChainExtension<Runtime> for KittiesChainExtension {
+	fn call<E>(func_id: u32, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let mut env = env.buf_in_buf_out();
+		match func_id {
+			QUERY_KITTY => {
+				env.charge_weight(RocksDbWeight::get().reads(1))?;
+				let kitty_id: KittyIndex = env.read_as()?;
+				let kitty = KittiesModule::kitties(kitty_id).map(|kitty| (kitty.dna, kitty.owner));
+				env.write(&kitty.encode(), false, None)?;
+			},
+			TRANSFER_KITTY => {
+				// The same storage accesses as the `transfer` extrinsic, plus the call filter.
+				env.charge_weight(RocksDbWeight::get().reads_writes(4, 4))?;
+				let (to, kitty_id): (AccountId, KittyIndex) = env.read_as()?;
+				// Contracts must not bypass maintenance mode, so apply the runtime's call filter
+				// to the equivalent extrinsic.
+				let call =
+					Call::KittiesModule(pallet_kitties::Call::transfer { to: to.clone(), kitty_id });
+				if !<Runtime as frame_system::Config>::BaseCallFilter::contains(&call) {
+					return Err(DispatchError::Other("kitty transfers are paused"))
+				}
+				let contract = env.ext().address().clone();
+				KittiesModule::do_transfer(&contract, &to, kitty_id)?;
+			},
+			QUERY_CLAIM => {
+				// Charge for the input by length before decoding it, like `seal_input`.
+				let len = env.in_len();
+				let per_byte = Schedule::get().host_fn_weights.input_per_byte;
+				env.charge_weight(
+					RocksDbWeight::get()
+						.reads(2)
+						.saturating_add(per_byte.saturating_mul(len.into())),
+				)?;
+				let max_claim_len = <Runtime as pallet_poe::Config>::AssetDepositBase::get() as u32;
+				if len > max_claim_len + Compact::<u32>::compact_len(&max_claim_len) as u32 {
+					return Err(DispatchError::Other("claim is too long"))
+				}
+				let proof: Vec<u8> = env.read_as_unbounded(len)?;
+				env.write(&PoeModule::proof_of(&proof).encode(), false, None)?;
+			},
+			_ => return Err(DispatchError::Other("unknown chain extension function")),
+		}
+		Ok(RetVal::Converging(0))
+	}
+}
@@ -9,14 +9,32 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
+use codec::{Decode, Encode, MaxEncodedLen};
 use sp_api::impl_runtime_apis;
+#[cfg(not(feature = "babe"))]
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{
+	crypto::KeyTypeId,
+	u32_trait::{_1, _2, _3, _5},
+	OpaqueMetadata,
+};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	traits::{
+		AccountIdLookup, BlakeTwo256, Block as BlockT, Extrinsic as ExtrinsicT, IdentifyAccount,
+		NumberFor, OpaqueKeys, Verify,
+	},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult, MultiSignature, RuntimeDebug, SaturatedConversion,
+};
+#[cfg(not(feature = "npos"))]
+use sp_runtime::traits::ConvertInto;
+#[cfg(feature = "npos")]
+use sp_runtime::curve::PiecewiseLinear;
+#[cfg(not(feature = "npos"))]
+use sp_staking::{
+	offence::{OffenceDetails, OnOffenceHandler},
+	SessionIndex,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -26,19 +44,25 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{KeyOwnerProofSystem, Randomness, StorageInfo},
+	traits::{
+		Contains, Currency, EqualPrivilegeOnly, Imbalance, InstanceFilter, KeyOwnerProofSystem,
+		OnUnbalanced, Randomness, StorageInfo,
+	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
-		IdentityFee, Weight,
+		IdentityFee, Weight, WeightToFeeCoefficient, WeightToFeeCoefficients,
+		WeightToFeePolynomial,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
+use frame_system::{EnsureOneOf, EnsureRoot};
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
-use pallet_transaction_payment::CurrencyAdapter;
+use pallet_transaction_payment::{CurrencyAdapter, Multiplier, TargetedFeeAdjustment};
+use smallvec::smallvec;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
-pub use sp_runtime::{Perbill, Permill};
+pub use sp_runtime::{FixedPointNumber, Perbill, Permill, Perquintill};
 
 pub use pallet_poe;
 /// Import the template pallet.
@@ -48,6 +72,19 @@ pub use pallet_kitties;
 
 pub use pallet_ocw;
 
+mod chain_extension;
+pub use chain_extension::KittiesChainExtension;
+
+#[cfg(all(not(feature = "babe"), not(feature = "npos")))]
+mod migrations;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "npos")]
+pub use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+#[cfg(feature = "npos")]
+pub use pallet_staking::StakerStatus;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -65,9 +102,15 @@ pub type Balance = u128;
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
+/// Identifier of an asset in `pallet_assets`.
+pub type AssetId = u32;
+
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
+/// A timestamp: milliseconds since the unix epoch.
+pub type Moment = u64;
+
 
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
@@ -86,10 +129,37 @@ pub mod opaque {
 	/// Opaque block identifier type.
 	pub type BlockId = generic::BlockId<Block>;
 
+	#[cfg(all(not(feature = "babe"), not(feature = "npos")))]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub aura: Aura,
+			pub grandpa: Grandpa,
+		}
+	}
+
+	#[cfg(all(not(feature = "babe"), feature = "npos"))]
 	impl_opaque_keys! {
 		pub struct SessionKeys {
 			pub aura: Aura,
 			pub grandpa: Grandpa,
+			pub im_online: ImOnline,
+		}
+	}
+
+	#[cfg(all(feature = "babe", not(feature = "npos")))]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub babe: Babe,
+			pub grandpa: Grandpa,
+		}
+	}
+
+	#[cfg(all(feature = "babe", feature = "npos"))]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub babe: Babe,
+			pub grandpa: Grandpa,
+			pub im_online: ImOnline,
 		}
 	}
 }
@@ -106,16 +176,18 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 102,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Bumped when pallets were inserted before existing ones in `construct_runtime!`, which
+	//   changed the indices that calls are encoded with.
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
 /// Blocks will be produced at a minimum duration defined by `SLOT_DURATION`.
 /// `SLOT_DURATION` is picked up by `pallet_timestamp` which is in turn picked
-/// up by `pallet_aura` to implement `fn slot_duration()`.
+/// up by `pallet_aura` (or `pallet_babe`) to implement `fn slot_duration()`.
 ///
 /// Change this to adjust the block time.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -129,6 +201,23 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+/// BABE epochs last one session.
+#[cfg(feature = "babe")]
+pub const EPOCH_DURATION_IN_SLOTS: u64 = HOURS as u64;
+
+/// 1 in 4 slots (on average, not counting collisions) will have a primary BABE block author.
+/// The remaining slots are filled by secondary authors, who also attach a VRF output.
+#[cfg(feature = "babe")]
+pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
+/// The BABE epoch configuration at genesis.
+#[cfg(feature = "babe")]
+pub const BABE_GENESIS_EPOCH_CONFIG: sp_consensus_babe::BabeEpochConfiguration =
+	sp_consensus_babe::BabeEpochConfiguration {
+		c: PRIMARY_PROBABILITY,
+		allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryVRFSlots,
+	};
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -152,8 +241,8 @@ parameter_types! {
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
-	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	/// The basic call filter to use in dispatchable: calls paused by maintenance mode are rejected.
+	type BaseCallFilter = MaintenanceMode;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -208,9 +297,43 @@ parameter_types! {
 	pub const MaxAuthorities: u32 = 32;
 }
 
+#[cfg(not(feature = "babe"))]
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
-	type DisabledValidators = ();
+	type DisabledValidators = Session;
+	type MaxAuthorities = MaxAuthorities;
+}
+
+#[cfg(feature = "babe")]
+parameter_types! {
+	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+	pub const ExpectedBlockTime: Moment = MILLISECS_PER_BLOCK;
+}
+
+#[cfg(feature = "babe")]
+impl pallet_babe::Config for Runtime {
+	type EpochDuration = EpochDuration;
+	type ExpectedBlockTime = ExpectedBlockTime;
+	// Epochs are changed by the session pallet.
+	type EpochChangeTrigger = pallet_babe::ExternalTrigger;
+	type DisabledValidators = Session;
+
+	type KeyOwnerProofSystem = Historical;
+
+	type KeyOwnerProof = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		pallet_babe::AuthorityId,
+	)>>::Proof;
+
+	type KeyOwnerIdentification = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		pallet_babe::AuthorityId,
+	)>>::IdentificationTuple;
+
+	type HandleEquivocation =
+		pallet_babe::EquivocationHandler<Self::KeyOwnerIdentification, Offences, ReportLongevity>;
+
+	type WeightInfo = ();
 	type MaxAuthorities = MaxAuthorities;
 }
 
@@ -218,7 +341,7 @@ impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -228,20 +351,216 @@ impl pallet_grandpa::Config for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation =
+		pallet_grandpa::EquivocationHandler<Self::KeyOwnerIdentification, Offences, ReportLongevity>;
 
 	type WeightInfo = ();
 	type MaxAuthorities = MaxAuthorities;
 }
 
+parameter_types! {
+	pub const UncleGenerations: BlockNumber = 0;
+}
+
+// The block author is needed to reward equivocation reporters, and with `npos` to reward
+// validators and track their liveness.
+impl pallet_authorship::Config for Runtime {
+	#[cfg(not(feature = "babe"))]
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+	#[cfg(feature = "babe")]
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Babe>;
+	// Uncles are not rewarded.
+	type UncleGenerations = UncleGenerations;
+	type FilterUncle = ();
+	#[cfg(not(feature = "npos"))]
+	type EventHandler = ();
+	#[cfg(feature = "npos")]
+	type EventHandler = (Staking, ImOnline);
+}
+
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = HOURS;
+	pub const SessionOffset: BlockNumber = 0;
+	/// Equivocation reports older than this many blocks are rejected.
+	pub const ReportLongevity: u64 = 6 * HOURS as u64;
+}
+
+/// When sessions end. With Aura they have a fixed length; with BABE they end with each epoch.
+#[cfg(not(feature = "babe"))]
+pub type SessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+/// When sessions end. With Aura they have a fixed length; with BABE they end with each epoch.
+#[cfg(feature = "babe")]
+pub type SessionRotation = Babe;
+
+// In the authority-set mode validators are managed by `pallet_validator_set` and identified by
+// their account. With `npos` they are elected by staking and identified by their stash.
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	#[cfg(not(feature = "npos"))]
+	type ValidatorIdOf = ConvertInto;
+	#[cfg(feature = "npos")]
+	type ValidatorIdOf = pallet_staking::StashOf<Self>;
+	type ShouldEndSession = SessionRotation;
+	type NextSessionRotation = SessionRotation;
+	#[cfg(not(feature = "npos"))]
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	#[cfg(feature = "npos")]
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
+#[cfg(not(feature = "npos"))]
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = AccountId;
+	type FullIdentificationOf = ConvertInto;
+}
+
+#[cfg(feature = "npos")]
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = pallet_staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
+}
+
+#[cfg(not(feature = "npos"))]
+parameter_types! {
+	pub const MinValidators: u32 = 1;
+	pub const MaxValidators: u32 = 100;
+}
+
+#[cfg(not(feature = "npos"))]
+impl pallet_validator_set::Config for Runtime {
+	type Event = Event;
+	type AddRemoveOrigin = EnsureRootOrHalfCouncil;
+	type MinValidators = MinValidators;
+	type MaxValidators = MaxValidators;
+}
+
+impl pallet_offences::Config for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	#[cfg(not(feature = "npos"))]
+	type OnOffenceHandler = DisableOffenders;
+	#[cfg(feature = "npos")]
+	type OnOffenceHandler = Staking;
+}
+
+/// Punishes reported offenders by disabling them for the rest of the session, so they can no
+/// longer author blocks or vote in GRANDPA, and removing them from the validator set from the
+/// next session on (unless that would drop below `MinValidators`).
+#[cfg(not(feature = "npos"))]
+pub struct DisableOffenders;
+
+#[cfg(not(feature = "npos"))]
+impl OnOffenceHandler<AccountId, pallet_session::historical::IdentificationTuple<Runtime>, Weight>
+	for DisableOffenders
+{
+	fn on_offence(
+		offenders: &[OffenceDetails<AccountId, pallet_session::historical::IdentificationTuple<Runtime>>],
+		_slash_fraction: &[Perbill],
+		_session: SessionIndex,
+	) -> Weight {
+		use frame_support::traits::Get;
+		for details in offenders {
+			let _ = Session::disable(&details.offender.0);
+			let _ = ValidatorSet::do_remove_validator(&details.offender.0);
+		}
+		RocksDbWeight::get().reads_writes(2 * offenders.len() as Weight, 3 * offenders.len() as Weight)
+	}
+}
+
+
+#[cfg(feature = "npos")]
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+#[cfg(feature = "npos")]
+parameter_types! {
+	// Six sessions, i.e. six hours, per era.
+	pub const SessionsPerEra: sp_staking::SessionIndex = 6;
+	// Seven days.
+	pub const BondingDuration: pallet_staking::EraIndex = 28;
+	// Slashes can be cancelled by governance for almost the whole bonding duration.
+	pub const SlashDeferDuration: pallet_staking::EraIndex = 27;
+	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
+	pub const MaxNominatorRewardedPerValidator: u32 = 256;
+}
+
+#[cfg(feature = "npos")]
+impl pallet_staking::Config for Runtime {
+	const MAX_NOMINATIONS: u32 = 16;
+	type Currency = Balances;
+	type UnixTime = Timestamp;
+	type CurrencyToVote = frame_support::traits::U128CurrencyToVote;
+	type RewardRemainder = Treasury;
+	type Event = Event;
+	// Slashed funds go to the treasury, rewards are minted.
+	type Slash = Treasury;
+	type Reward = ();
+	type SessionsPerEra = SessionsPerEra;
+	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	// Root or three quarters of the council can cancel a deferred slash.
+	type SlashCancelOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_3, sp_core::u32_trait::_4, AccountId, CouncilCollective>,
+	>;
+	type SessionInterface = Self;
+	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type NextNewSession = Session;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
+	// The validator set is small, so the election runs on-chain at the end of each era.
+	type ElectionProvider = frame_election_provider_support::onchain::OnChainSequentialPhragmen<Self>;
+	type GenesisElectionProvider =
+		frame_election_provider_support::onchain::OnChainSequentialPhragmen<Self>;
+	type SortedListProvider = pallet_staking::UseNominatorsMap<Self>;
+	type WeightInfo = pallet_staking::weights::SubstrateWeight<Runtime>;
+}
+
+#[cfg(feature = "npos")]
+impl frame_election_provider_support::onchain::Config for Runtime {
+	type Accuracy = Perbill;
+	type DataProvider = Staking;
+}
+
+#[cfg(feature = "npos")]
+parameter_types! {
+	pub const ImOnlineUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+}
+
+#[cfg(feature = "npos")]
+impl pallet_im_online::Config for Runtime {
+	type AuthorityId = ImOnlineId;
+	type Event = Event;
+	type NextSessionRotation = SessionRotation;
+	type ValidatorSet = Historical;
+	type ReportUnresponsiveness = Offences;
+	type UnsignedPriority = ImOnlineUnsignedPriority;
+	type WeightInfo = pallet_im_online::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const MinimumPeriod: u64 = SLOT_DURATION / 2;
 }
 
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
+	type Moment = Moment;
+	#[cfg(not(feature = "babe"))]
 	type OnTimestampSet = Aura;
+	#[cfg(feature = "babe")]
+	type OnTimestampSet = Babe;
 	type MinimumPeriod = MinimumPeriod;
 	type WeightInfo = ();
 }
@@ -285,9 +604,8 @@ impl pallet_nicks::Config for Runtime {
 	// No action is taken when deposits are forfeited.
 	type Slashed = ();
 
-	// Configure the FRAME System Root origin as the Nick pallet admin.
-	// https://docs.substrate.io/rustdocs/latest/frame_system/enum.RawOrigin.html#variant.Root
-	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	// Root or a majority of the council administers nicks.
+	type ForceOrigin = EnsureRootOrHalfCouncil;
 
 	// Use the MinNickLength from the parameter_types block.
 	type MinLength = MinNickLength;
@@ -299,17 +617,474 @@ impl pallet_nicks::Config for Runtime {
 	type Event = Event;
 }
 
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 8.
+	pub const ProxyDepositBase: Balance = 10_000;
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = 1_000;
+	pub const MaxProxies: u16 = 32;
+	pub const AnnouncementDepositBase: Balance = 10_000;
+	pub const AnnouncementDepositFactor: Balance = 2_000;
+	pub const MaxPending: u16 = 32;
+}
+
+/// The kinds of calls a proxy account may make on behalf of its delegator.
+#[derive(
+	Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, MaxEncodedLen, scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Any call that cannot move balances, kitties or claims to another account.
+	NonTransfer,
+	/// Only kitty calls, e.g. a game client's session hot key.
+	Kitties,
+	/// Only proof-of-existence calls.
+	Poe,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// An allowlist, so that calls added later stay forbidden until they are reviewed.
+			ProxyType::NonTransfer => match c {
+				Call::System(..) |
+				Call::Timestamp(..) |
+				Call::Session(..) |
+				Call::TemplateModule(..) |
+				Call::OcwModule(..) |
+				Call::Nicks(..) |
+				Call::Utility(..) |
+				Call::Proxy(..) |
+				Call::Multisig(..) |
+				Call::Council(..) |
+				Call::Democracy(..) |
+				Call::Treasury(..) => true,
+				#[cfg(feature = "npos")]
+				Call::Staking(..) => true,
+				Call::KittiesModule(pallet_kitties::Call::create { .. }) |
+				Call::KittiesModule(pallet_kitties::Call::breed { .. }) => true,
+				// A kitty listed for any price can be bought by an accomplice, so only delisting.
+				Call::KittiesModule(pallet_kitties::Call::sale { price: None, .. }) |
+				Call::KittiesModule(pallet_kitties::Call::sale_in_asset {
+					price: None, ..
+				}) => true,
+				// Choosing the fee asset only spends the account's own fees.
+				Call::AssetFees(pallet_asset_fees::Call::set_fee_asset { .. }) => true,
+				Call::PoeModule(pallet_poe::Call::transfer_claim { .. }) |
+				Call::PoeModule(pallet_poe::Call::propose_claim_action {
+					action: pallet_poe::ClaimAction::Transfer(..),
+					..
+				}) => false,
+				Call::PoeModule(pallet_poe::Call::approve_claim_action { proof }) => !matches!(
+					PoeModule::pending_actions(proof).map(|pending| pending.action),
+					Some(pallet_poe::ClaimAction::Transfer(..))
+				),
+				Call::PoeModule(..) => true,
+				// Calls and instantiations carry value, and the chain extension can transfer kitties.
+				Call::Contracts(..) => false,
+				_ => false,
+			},
+			// Batches are allowed; every call inside is filtered again.
+			ProxyType::Kitties => matches!(c, Call::KittiesModule(..) | Call::Utility(..)),
+			ProxyType::Poe => matches!(c, Call::PoeModule(..) | Call::Utility(..)),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = MaxPending;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const MultisigDepositBase: Balance = 10_000;
+	// Additional storage item size of 32 bytes.
+	pub const MultisigDepositFactor: Balance = 1_000;
+	pub const MaxSignatories: u16 = 100;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+}
+
 parameter_types! {
-	pub const TransactionByteFee: Balance = 1;
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Root, or more than half of the council. Used for pallet admin origins.
+pub type EnsureRootOrHalfCouncil = EnsureOneOf<
+	AccountId,
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+>;
+
+parameter_types! {
+	pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+	pub const VotingPeriod: BlockNumber = 7 * DAYS;
+	pub const FastTrackVotingPeriod: BlockNumber = 3 * HOURS;
+	pub const EnactmentPeriod: BlockNumber = DAYS;
+	pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+	pub const MinimumDeposit: Balance = 1_000_000;
+	pub const PreimageByteDeposit: Balance = 100;
+	pub const InstantAllowed: bool = true;
+	pub const MaxVotes: u32 = 100;
+	pub const MaxProposals: u32 = 100;
+}
+
+impl pallet_democracy::Config for Runtime {
+	type Proposal = Call;
+	type Event = Event;
+	type Currency = Balances;
+	type EnactmentPeriod = EnactmentPeriod;
+	type LaunchPeriod = LaunchPeriod;
+	type VotingPeriod = VotingPeriod;
+	type MinimumDeposit = MinimumDeposit;
+	/// A straight majority of the council can decide what their next motion is.
+	type ExternalOrigin =
+		pallet_collective::EnsureProportionAtLeast<_1, _2, AccountId, CouncilCollective>;
+	/// A super-majority can have the next scheduled referendum be a straight majority-carries vote.
+	type ExternalMajorityOrigin =
+		pallet_collective::EnsureProportionAtLeast<_3, _5, AccountId, CouncilCollective>;
+	/// A unanimous council can have the next scheduled referendum be a straight default-carries
+	/// (NTB) vote.
+	type ExternalDefaultOrigin =
+		pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+	/// Two thirds of the council can have an external proposal tabled immediately for a fast
+	/// vote.
+	type FastTrackOrigin =
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>;
+	type InstantOrigin =
+		pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+	type InstantAllowed = InstantAllowed;
+	type FastTrackVotingPeriod = FastTrackVotingPeriod;
+	// To cancel a proposal which has been passed, 2/3 of the council must agree to it.
+	type CancellationOrigin =
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>;
+	type BlacklistOrigin = EnsureRoot<AccountId>;
+	// To cancel a proposal before it has been passed, the council must be unanimous or
+	// Root must agree.
+	type CancelProposalOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>,
+	>;
+	// Any single council member may veto a coming council proposal, however they can
+	// only do it once and it lasts only for the cool-off period.
+	type VetoOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type CooloffPeriod = CooloffPeriod;
+	type PreimageByteDeposit = PreimageByteDeposit;
+	type OperationalPreimageOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type Slash = Treasury;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type MaxVotes = MaxVotes;
+	type WeightInfo = pallet_democracy::weights::SubstrateWeight<Runtime>;
+	type MaxProposals = MaxProposals;
+}
+
+parameter_types! {
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 100_000;
+	pub const SpendPeriod: BlockNumber = DAYS;
+	pub const Burn: Permill = Permill::from_percent(1);
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const MaxApprovals: u32 = 100;
+}
+
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_3, _5, AccountId, CouncilCollective>,
+	>;
+	type RejectOrigin = EnsureRootOrHalfCouncil;
+	type Event = Event;
+	type OnSlash = ();
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = MaxApprovals;
+}
+
+/// Pallets that keep working in maintenance mode: block production, finality, governance and
+/// sudo.
+pub struct UnpausablePallets;
+
+impl Contains<Vec<u8>> for UnpausablePallets {
+	fn contains(pallet_name: &Vec<u8>) -> bool {
+		matches!(
+			pallet_name.as_slice(),
+			b"System" |
+				b"Timestamp" |
+				b"Authorship" |
+				b"Session" |
+				b"Aura" |
+				b"Babe" |
+				b"Grandpa" |
+				b"ImOnline" |
+				b"Sudo" |
+				b"Council" |
+				b"Democracy" |
+				b"Scheduler"
+		)
+	}
+}
+
+parameter_types! {
+	pub const MaxPalletNameLength: u32 = 64;
+}
+
+impl pallet_maintenance::Config for Runtime {
+	type Event = Event;
+	type PauseOrigin = EnsureRootOrHalfCouncil;
+	type UnpausablePallets = UnpausablePallets;
+	type MaxNameLength = MaxPalletNameLength;
+}
+
+/// Game actions a sponsor may pay the fees of.
+///
+/// Accepting a sponsor's offer is always paid for by that sponsor and needs no entry here.
+pub struct SponsoredCalls;
+
+impl Contains<Call> for SponsoredCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::KittiesModule(pallet_kitties::Call::create { .. }) |
+				Call::PoeModule(pallet_poe::Call::create_claim { .. })
+		)
+	}
+}
+
+parameter_types! {
+	pub const SponsorshipRateLimitPeriod: BlockNumber = HOURS;
+	pub const MaxSponsoredPerPeriod: u32 = 10;
+	// Covers the stake of one kitty and keeps the existential deposit free.
+	pub SponsorshipEndowment: Balance = StakeAmountForKitty::get() + ExistentialDeposit::get();
+}
+
+impl pallet_sponsorship::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type SponsoredCalls = SponsoredCalls;
+	type Currency = Balances;
+	type Endowment = SponsorshipEndowment;
+	type RateLimitPeriod = SponsorshipRateLimitPeriod;
+	type MaxSponsoredPerPeriod = MaxSponsoredPerPeriod;
+}
+
+/// The storage key of the sudo key.
+pub fn sudo_key_storage_key() -> Vec<u8> {
+	use frame_support::{StorageHasher, Twox128};
+	[Twox128::hash(b"Sudo"), Twox128::hash(b"Key")].concat()
+}
+
+/// The call that retires `pallet_sudo` once the council and democracy are running.
+///
+/// Dispatched as Root through a referendum, it deletes the sudo key. The key then reads as the
+/// all-zero account, which nobody can sign for, so every sudo call fails with `RequireSudo`.
+/// The pallet itself can be dropped from `construct_runtime!` in a later runtime upgrade.
+pub fn remove_sudo_call() -> Call {
+	Call::System(frame_system::Call::kill_storage { keys: vec![sudo_key_storage_key()] })
+}
+
+parameter_types! {
+	/// The fee of an extrinsic with `ExtrinsicBaseWeight` weight, before the fee multiplier.
+	pub const ExtrinsicBaseFee: Balance = 10_000_000;
+	pub const TransactionByteFee: Balance = 10_000;
 	pub OperationalFeeMultiplier: u8 = 5;
+	/// The portion of the normal block weight the fee multiplier steers towards.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	/// How fast the fee multiplier reacts: a full block raises it by about 0.15%, so an hour
+	/// of full blocks raises fees about 2.5-fold.
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(2, 1_000);
+	/// Quiet periods lower fees to no less than a tenth of their base value, so a spammer
+	/// cannot wait for them to become negligible.
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 10);
+}
+
+/// Converts a weight into a fee, charging `ExtrinsicBaseFee` for `ExtrinsicBaseWeight`.
+///
+/// The base fee is charged even for calls declaring zero weight.
+pub struct WeightToFee;
+
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		let p = ExtrinsicBaseFee::get();
+		let q = Balance::from(ExtrinsicBaseWeight::get());
+		smallvec![WeightToFeeCoefficient {
+			degree: 1,
+			negative: false,
+			coeff_frac: Perbill::from_rational(p % q, q),
+			coeff_integer: p / q,
+		}]
+	}
+}
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Pays funds to the author of the current block.
+pub struct Author;
+
+impl OnUnbalanced<NegativeImbalance> for Author {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+		Balances::resolve_creating(&Authorship::author(), amount);
+	}
+}
+
+/// Splits transaction fees 80% to the treasury and 20% to the block author, who also gets
+/// all of the tip.
+pub struct DealWithFees;
+
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			let (to_treasury, mut to_author) = fees.ration(80, 20);
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut to_author);
+			}
+			Treasury::on_unbalanced(to_treasury);
+			Author::on_unbalanced(to_author);
+		}
+	}
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = pallet_asset_fees::AssetConversionAdapter<Runtime>;
 	type TransactionByteFee = TransactionByteFee;
 	type OperationalFeeMultiplier = OperationalFeeMultiplier;
-	type WeightToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ();
+	type WeightToFee = WeightToFee;
+	type FeeMultiplierUpdate =
+		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
+}
+
+parameter_types! {
+	pub const AssetDeposit: Balance = 100_000;
+	pub const AssetApprovalDeposit: Balance = 1_000;
+	pub const AssetStringLimit: u32 = 50;
+	pub const AssetMetadataDepositBase: Balance = 10_000;
+	pub const AssetMetadataDepositPerByte: Balance = 10;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = AssetMetadataDepositBase;
+	type MetadataDepositPerByte = AssetMetadataDepositPerByte;
+	type ApprovalDeposit = AssetApprovalDeposit;
+	type StringLimit = AssetStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// Fees paid in assets go to the treasury, which must hold each fee asset's minimum
+	/// balance before governance can approve the asset.
+	pub TreasuryAccount: AccountId = Treasury::account_id();
+	/// Same split as `DealWithFees`: 20% of the fee, plus the whole tip, goes to the author.
+	pub const AssetFeeAuthorShare: Permill = Permill::from_percent(20);
+}
+
+/// The author of the current block.
+pub struct BlockAuthor;
+
+impl frame_support::traits::Get<AccountId> for BlockAuthor {
+	fn get() -> AccountId {
+		Authorship::author()
+	}
+}
+
+impl pallet_asset_fees::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type NativeAdapter = CurrencyAdapter<Balances, DealWithFees>;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type FeeCollector = TreasuryAccount;
+	type BlockAuthor = BlockAuthor;
+	type AuthorShare = AssetFeeAuthorShare;
 }
 
 impl pallet_sudo::Config for Runtime {
@@ -317,56 +1092,293 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const RegistryNamespaceDeposit: Balance = 10_000;
+	pub const RegistryDepositBase: Balance = 1_000;
+	pub const RegistryDepositPerByte: Balance = 10;
+	pub const RegistryMaxKeyLength: u32 = 64;
+	pub const RegistryMaxValueLength: u32 = 1024;
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type Event = Event;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type NamespaceDeposit = RegistryNamespaceDeposit;
+	type DepositBase = RegistryDepositBase;
+	type DepositPerByte = RegistryDepositPerByte;
+	type MaxKeyLength = RegistryMaxKeyLength;
+	type MaxValueLength = RegistryMaxValueLength;
+}
+
+parameter_types! {
+	pub const MaxMetadataLength: u32 = 256;
+	pub const ExpiryDeposit: Balance = 10_000;
+	pub const PruneReward: Balance = 1_000;
+	pub const MaxSigners: u32 = 16;
+	pub const ActionPeriod: BlockNumber = 7 * DAYS;
+	pub const TombstonePeriod: BlockNumber = 30 * DAYS;
 }
 
 impl pallet_poe::Config for Runtime {
 	type Event = Event;
 	type AssetDepositBase = AssetDepositBase;
+	type TimeProvider = Timestamp;
+	type Currency = Balances;
+	type MaxMetadataLength = MaxMetadataLength;
+	type ExpiryDeposit = ExpiryDeposit;
+	type PruneReward = PruneReward;
+	type MaxSigners = MaxSigners;
+	type ActionPeriod = ActionPeriod;
+	type TombstonePeriod = TombstonePeriod;
 }
 
 parameter_types! {
 	pub const StakeAmountForKitty: u128 = 1_000;
+	pub const KittyBlocksPerDay: BlockNumber = DAYS;
 }
 
+/// Index type of kitties, shared with the node-side analytics RPC.
+pub type KittyIndex = u32;
+
 impl pallet_kitties::Config for Runtime {
 	type Event = Event;
+	// BABE's VRF outputs cannot be biased by the block author the way the parent block hashes
+	// behind the collective flip can.
+	#[cfg(not(feature = "babe"))]
 	type Randomness = RandomnessCollectiveFlip;
+	#[cfg(feature = "babe")]
+	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
 	type Currency = Balances;
-	type KittyIndex = u32;
+	type KittyIndex = KittyIndex;
+	type AssetId = AssetId;
+	type Assets = Assets;
 	type StakeAmountForKitty = StakeAmountForKitty;
+	type BlocksPerDay = KittyBlocksPerDay;
 }
 
-impl pallet_ocw::Config for Runtime {
+/// Whether dry-run contract calls through the RPC return the contracts' debug messages.
+const CONTRACTS_DEBUG_OUTPUT: bool = true;
+
+parameter_types! {
+	pub const ContractDeposit: Balance = 100_000;
+	pub const DeletionQueueDepth: u32 = 128;
+	/// Removing the storage of terminated contracts gets at most a tenth of each block.
+	pub DeletionWeightLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
 	type Event = Event;
+	type Call = Call;
+	/// Contracts may not dispatch runtime calls: the calls they need are exposed through the
+	/// chain extension, which stays stable across runtime upgrades.
+	type CallFilter = frame_support::traits::Nothing;
+	type ContractDeposit = ContractDeposit;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = KittiesChainExtension;
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
 }
 
+parameter_types! {
+	pub const MaxNumbers: u32 = 64;
+	pub const OcwUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	pub const MaxFeeds: u32 = 32;
+	pub const MaxFeedUrlLength: u32 = 256;
+	pub const MaxOracles: u32 = 16;
+	pub const OcwGracePeriod: BlockNumber = 3;
+	pub const OracleBond: Balance = 1_000_000;
+	pub const OracleRewardPerRound: Balance = 1_000;
+	pub const OracleTolerance: Permill = Permill::from_percent(5);
+	pub const OracleMedianHistory: u32 = 64;
+	pub const OracleOutlierSlash: Balance = 100_000;
+	pub const OracleMissedRoundSlash: Balance = 10_000;
+	pub const MaxKittyRenders: u32 = 16;
+	pub const OcwPalletId: PalletId = PalletId(*b"py/oracl");
+	pub const ChallengerReward: Permill = Permill::from_percent(20);
+}
+
+/// Exposes kitty DNA to the offchain worker so it can render and attest kitty images.
+pub struct KittyDnaSource;
 
-// Create the runtime by composing the FRAME pallets that were previously configured.
-construct_runtime!(
-	pub enum Runtime where
-		Block = Block,
-		NodeBlock = opaque::Block,
-		UncheckedExtrinsic = UncheckedExtrinsic
-	{
-		System: frame_system,
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
-		Timestamp: pallet_timestamp,
-		Aura: pallet_aura,
-		Grandpa: pallet_grandpa,
-		Balances: pallet_balances,
-		TransactionPayment: pallet_transaction_payment,
-		Sudo: pallet_sudo,
-		// Include the custom logic from the pallet-template in the runtime.
-		TemplateModule: pallet_template,
-		PoeModule: pallet_poe,
-		KittiesModule:pallet_kitties,
-		OcwModule:pallet_ocw,
-		Nicks: pallet_nicks
+impl pallet_ocw::render::KittyDna<KittyIndex> for KittyDnaSource {
+	fn last_kitty_id() -> Option<KittyIndex> {
+		KittiesModule::kitties_count()
 	}
-);
+
+	fn dna(kitty_id: KittyIndex) -> Option<[u8; 16]> {
+		KittiesModule::kitties(kitty_id).map(|kitty| kitty.dna)
+	}
+}
+
+impl pallet_ocw::Config for Runtime {
+	type AuthorityId = pallet_ocw::crypto::OcwAuthId;
+	type Event = Event;
+	type Call = Call;
+	type MaxNumbers = MaxNumbers;
+	type UnsignedPriority = OcwUnsignedPriority;
+	type ManagerOrigin = EnsureRootOrHalfCouncil;
+	type MaxFeeds = MaxFeeds;
+	type MaxUrlLength = MaxFeedUrlLength;
+	type MaxOracles = MaxOracles;
+	type GracePeriod = OcwGracePeriod;
+	type Currency = Balances;
+	type OracleBond = OracleBond;
+	type RewardPerRound = OracleRewardPerRound;
+	type Tolerance = OracleTolerance;
+	type MedianHistory = OracleMedianHistory;
+	type OutlierSlash = OracleOutlierSlash;
+	type MissedRoundSlash = OracleMissedRoundSlash;
+	// Slashed bonds refill the pot that pays oracle rewards.
+	type OnSlash = pallet_ocw::SlashToRewardPot<Runtime>;
+	type PalletId = OcwPalletId;
+	type ChallengerReward = ChallengerReward;
+	type KittyIndex = KittyIndex;
+	type Kitties = KittyDnaSource;
+	type MaxRenders = MaxKittyRenders;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		index: Index,
+	) -> Option<(Call, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+		// Mortal for half of the block hash history, like transactions built by wallets.
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|c| c / 2)
+			.unwrap_or(2) as u64;
+		let current_block = System::block_number().saturated_into::<u64>().saturating_sub(1);
+		let tip = 0;
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(index),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_sponsorship::ChargeSponsoredTransactionPayment::<Runtime>::from(tip),
+		);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let address = sp_runtime::MultiAddress::Id(account);
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (address, signature, extra)))
+	}
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+
+/// Creates the runtime by composing the FRAME pallets that were previously configured, with the
+/// given block authoring pallet, validator management pallets and, with `npos`, `ImOnline`.
+macro_rules! construct_node_runtime {
+	(
+		consensus: { $($consensus:tt)* },
+		validators: { $($validators:tt)* },
+		liveness: { $($liveness:tt)* } $(,)?
+	) => {
+		construct_runtime!(
+			pub enum Runtime where
+				Block = Block,
+				NodeBlock = opaque::Block,
+				UncheckedExtrinsic = UncheckedExtrinsic
+			{
+				System: frame_system,
+				RandomnessCollectiveFlip: pallet_randomness_collective_flip,
+				Timestamp: pallet_timestamp,
+				Authorship: pallet_authorship,
+				Balances: pallet_balances,
+				// The validator management pallets must come before Session, so that the genesis
+				// validators are known when the first session starts, and Session before the
+				// consensus pallets so that their authorities are set from the session keys.
+				$($validators)*
+				Session: pallet_session,
+				Historical: pallet_session::historical::{Pallet},
+				$($consensus)*
+				Grandpa: pallet_grandpa,
+				$($liveness)*
+				TransactionPayment: pallet_transaction_payment,
+				Sudo: pallet_sudo,
+				// Include the custom logic from the pallet-template in the runtime.
+				TemplateModule: pallet_template,
+				PoeModule: pallet_poe,
+				KittiesModule:pallet_kitties,
+				OcwModule:pallet_ocw,
+				Nicks: pallet_nicks,
+				Utility: pallet_utility,
+				Proxy: pallet_proxy,
+				Multisig: pallet_multisig,
+				Scheduler: pallet_scheduler,
+				Council: pallet_collective::<Instance1>,
+				Democracy: pallet_democracy,
+				Treasury: pallet_treasury,
+				Offences: pallet_offences,
+				MaintenanceMode: pallet_maintenance,
+				Sponsorship: pallet_sponsorship,
+				Assets: pallet_assets,
+				AssetFees: pallet_asset_fees,
+				Contracts: pallet_contracts,
+			}
+		);
+	};
+}
+
+// Aura block authoring with validators managed by `ValidatorSet`.
+#[cfg(all(not(feature = "babe"), not(feature = "npos")))]
+construct_node_runtime! {
+	consensus: { Aura: pallet_aura, },
+	validators: { ValidatorSet: pallet_validator_set, },
+	liveness: {},
+}
+
+// Aura block authoring with validators elected by `Staking`.
+#[cfg(all(not(feature = "babe"), feature = "npos"))]
+construct_node_runtime! {
+	consensus: { Aura: pallet_aura, },
+	validators: { Staking: pallet_staking, },
+	liveness: { ImOnline: pallet_im_online, },
+}
+
+// BABE block authoring with validators managed by `ValidatorSet`.
+#[cfg(all(feature = "babe", not(feature = "npos")))]
+construct_node_runtime! {
+	consensus: { Babe: pallet_babe, },
+	validators: { ValidatorSet: pallet_validator_set, },
+	liveness: {},
+}
+
+// BABE block authoring with validators elected by `Staking`.
+#[cfg(all(feature = "babe", feature = "npos"))]
+construct_node_runtime! {
+	consensus: { Babe: pallet_babe, },
+	validators: { Staking: pallet_staking, },
+	liveness: { ImOnline: pallet_im_online, },
+}
 
 /// The address format for describing accounts.
 pub type Address = sp_runtime::MultiAddress<AccountId, ()>;
@@ -382,10 +1394,21 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	// Charges the fee like `ChargeTransactionPayment`, but to the sender's sponsor for
+	// sponsored calls.
+	pallet_sponsorship::ChargeSponsoredTransactionPayment<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
+/// Migrations for chains started before `Session` and `ValidatorSet` were added. Chains built
+/// with BABE or `Staking` start from a fresh genesis, so they have none.
+#[cfg(all(not(feature = "babe"), not(feature = "npos")))]
+type Migrations = migrations::SeedSessionValidators;
+#[cfg(any(feature = "babe", feature = "npos"))]
+type Migrations = ();
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -393,66 +1416,274 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPallets,
+	Migrations,
 >;
 
-impl_runtime_apis! {
-	impl sp_api::Core<Block> for Runtime {
-		fn version() -> RuntimeVersion {
-			VERSION
-		}
-
-		fn execute_block(block: Block) {
-			Executive::execute_block(block);
-		}
-
-		fn initialize_block(header: &<Block as BlockT>::Header) {
-			Executive::initialize_block(header)
-		}
-	}
-
-	impl sp_api::Metadata<Block> for Runtime {
-		fn metadata() -> OpaqueMetadata {
-			OpaqueMetadata::new(Runtime::metadata().into())
+/// Implements the runtime APIs shared by every build, plus the given block authoring API.
+macro_rules! impl_node_runtime_apis {
+	($($consensus_api:tt)*) => {
+		impl_runtime_apis! {
+			$($consensus_api)*
+
+			impl sp_api::Core<Block> for Runtime {
+				fn version() -> RuntimeVersion {
+					VERSION
+				}
+
+				fn execute_block(block: Block) {
+					Executive::execute_block(block);
+				}
+
+				fn initialize_block(header: &<Block as BlockT>::Header) {
+					Executive::initialize_block(header)
+				}
+			}
+
+			impl sp_api::Metadata<Block> for Runtime {
+				fn metadata() -> OpaqueMetadata {
+					OpaqueMetadata::new(Runtime::metadata().into())
+				}
+			}
+
+			impl sp_block_builder::BlockBuilder<Block> for Runtime {
+				fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
+					Executive::apply_extrinsic(extrinsic)
+				}
+
+				fn finalize_block() -> <Block as BlockT>::Header {
+					Executive::finalize_block()
+				}
+
+				fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
+					data.create_extrinsics()
+				}
+
+				fn check_inherents(
+					block: Block,
+					data: sp_inherents::InherentData,
+				) -> sp_inherents::CheckInherentsResult {
+					data.check_extrinsics(&block)
+				}
+			}
+
+			impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
+				fn validate_transaction(
+					source: TransactionSource,
+					tx: <Block as BlockT>::Extrinsic,
+					block_hash: <Block as BlockT>::Hash,
+				) -> TransactionValidity {
+					Executive::validate_transaction(source, tx, block_hash)
+				}
+			}
+
+			impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
+				fn offchain_worker(header: &<Block as BlockT>::Header) {
+					Executive::offchain_worker(header)
+				}
+			}
+
+			impl sp_session::SessionKeys<Block> for Runtime {
+				fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
+					opaque::SessionKeys::generate(seed)
+				}
+
+				fn decode_session_keys(
+					encoded: Vec<u8>,
+				) -> Option<Vec<(Vec<u8>, KeyTypeId)>> {
+					opaque::SessionKeys::decode_into_raw_public_keys(&encoded)
+				}
+			}
+
+			impl fg_primitives::GrandpaApi<Block> for Runtime {
+				fn grandpa_authorities() -> GrandpaAuthorityList {
+					Grandpa::grandpa_authorities()
+				}
+
+				fn current_set_id() -> fg_primitives::SetId {
+					Grandpa::current_set_id()
+				}
+
+				fn submit_report_equivocation_unsigned_extrinsic(
+					equivocation_proof: fg_primitives::EquivocationProof<
+						<Block as BlockT>::Hash,
+						NumberFor<Block>,
+					>,
+					key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+				) -> Option<()> {
+					let key_owner_proof = key_owner_proof.decode()?;
+
+					Grandpa::submit_unsigned_equivocation_report(
+						equivocation_proof,
+						key_owner_proof,
+					)
+				}
+
+				fn generate_key_ownership_proof(
+					_set_id: fg_primitives::SetId,
+					authority_id: GrandpaId,
+				) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
+					Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+						.map(|p| p.encode())
+						.map(fg_primitives::OpaqueKeyOwnershipProof::new)
+				}
+			}
+
+			impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index> for Runtime {
+				fn account_nonce(account: AccountId) -> Index {
+					System::account_nonce(account)
+				}
+			}
+
+			impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber, Moment> for Runtime {
+				fn verify_inclusion(
+					root: sp_core::H256,
+					leaf: sp_core::H256,
+					index: u32,
+					proof: Vec<sp_core::H256>,
+				) -> bool {
+					PoeModule::verify_inclusion(root, leaf, index, proof)
+				}
+
+				fn proof_of(
+					proof: Vec<u8>,
+				) -> Option<pallet_poe_runtime_api::ProofInfo<AccountId, BlockNumber, Moment>> {
+					PoeModule::proof_of(&proof).map(|(owner, block_number, timestamp)| {
+						pallet_poe_runtime_api::ProofInfo { owner, block_number, timestamp }
+					})
+				}
+
+				fn claim_storage_keys(proof: Vec<u8>) -> Vec<Vec<u8>> {
+					PoeModule::claim_storage_keys(&proof)
+				}
+			}
+
+			impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash>
+				for Runtime
+			{
+				fn call(
+					origin: AccountId,
+					dest: AccountId,
+					value: Balance,
+					gas_limit: u64,
+					input_data: Vec<u8>,
+				) -> pallet_contracts_primitives::ContractExecResult {
+					Contracts::bare_call(origin, dest, value, gas_limit, input_data, CONTRACTS_DEBUG_OUTPUT)
+				}
+
+				fn instantiate(
+					origin: AccountId,
+					endowment: Balance,
+					gas_limit: u64,
+					code: pallet_contracts_primitives::Code<Hash>,
+					data: Vec<u8>,
+					salt: Vec<u8>,
+				) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId> {
+					Contracts::bare_instantiate(
+						origin,
+						endowment,
+						gas_limit,
+						code,
+						data,
+						salt,
+						CONTRACTS_DEBUG_OUTPUT,
+					)
+				}
+
+				fn get_storage(
+					address: AccountId,
+					key: [u8; 32],
+				) -> pallet_contracts_primitives::GetStorageResult {
+					Contracts::get_storage(address, key)
+				}
+			}
+
+			impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
+				fn query_info(
+					uxt: <Block as BlockT>::Extrinsic,
+					len: u32,
+				) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
+					TransactionPayment::query_info(uxt, len)
+				}
+				fn query_fee_details(
+					uxt: <Block as BlockT>::Extrinsic,
+					len: u32,
+				) -> pallet_transaction_payment::FeeDetails<Balance> {
+					TransactionPayment::query_fee_details(uxt, len)
+				}
+			}
+
+			#[cfg(feature = "runtime-benchmarks")]
+			impl frame_benchmarking::Benchmark<Block> for Runtime {
+				fn benchmark_metadata(extra: bool) -> (
+					Vec<frame_benchmarking::BenchmarkList>,
+					Vec<frame_support::traits::StorageInfo>,
+				) {
+					use frame_benchmarking::{list_benchmark, baseline, Benchmarking, BenchmarkList};
+					use frame_support::traits::StorageInfoTrait;
+					use frame_system_benchmarking::Pallet as SystemBench;
+					use baseline::Pallet as BaselineBench;
+
+					let mut list = Vec::<BenchmarkList>::new();
+
+					list_benchmark!(list, extra, frame_benchmarking, BaselineBench::<Runtime>);
+					list_benchmark!(list, extra, frame_system, SystemBench::<Runtime>);
+					list_benchmark!(list, extra, pallet_balances, Balances);
+					list_benchmark!(list, extra, pallet_timestamp, Timestamp);
+					list_benchmark!(list, extra, pallet_template, TemplateModule);
+					list_benchmark!(list, extra, pallet_utility, Utility);
+					list_benchmark!(list, extra, pallet_proxy, Proxy);
+					list_benchmark!(list, extra, pallet_multisig, Multisig);
+
+					let storage_info = AllPalletsWithSystem::storage_info();
+
+					return (list, storage_info)
+				}
+
+				fn dispatch_benchmark(
+					config: frame_benchmarking::BenchmarkConfig
+				) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+					use frame_benchmarking::{baseline, Benchmarking, BenchmarkBatch, add_benchmark, TrackedStorageKey};
+
+					use frame_system_benchmarking::Pallet as SystemBench;
+					use baseline::Pallet as BaselineBench;
+
+					impl frame_system_benchmarking::Config for Runtime {}
+					impl baseline::Config for Runtime {}
+
+					let whitelist: Vec<TrackedStorageKey> = vec![
+						// Block Number
+						hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
+						// Total Issuance
+						hex_literal::hex!("c2261276cc9d1f8598ea4b6a74b15c2f57c875e4cff74148e4628f264b974c80").to_vec().into(),
+						// Execution Phase
+						hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a").to_vec().into(),
+						// Event Count
+						hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850").to_vec().into(),
+						// System Events
+						hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7").to_vec().into(),
+					];
+
+					let mut batches = Vec::<BenchmarkBatch>::new();
+					let params = (&config, &whitelist);
+
+					add_benchmark!(params, batches, frame_benchmarking, BaselineBench::<Runtime>);
+					add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
+					add_benchmark!(params, batches, pallet_balances, Balances);
+					add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+					add_benchmark!(params, batches, pallet_template, TemplateModule);
+					add_benchmark!(params, batches, pallet_utility, Utility);
+					add_benchmark!(params, batches, pallet_proxy, Proxy);
+					add_benchmark!(params, batches, pallet_multisig, Multisig);
+
+					Ok(batches)
+				}
+			}
 		}
-	}
-
-	impl sp_block_builder::BlockBuilder<Block> for Runtime {
-		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
-			Executive::apply_extrinsic(extrinsic)
-		}
-
-		fn finalize_block() -> <Block as BlockT>::Header {
-			Executive::finalize_block()
-		}
-
-		fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
-			data.create_extrinsics()
-		}
-
-		fn check_inherents(
-			block: Block,
-			data: sp_inherents::InherentData,
-		) -> sp_inherents::CheckInherentsResult {
-			data.check_extrinsics(&block)
-		}
-	}
-
-	impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
-		fn validate_transaction(
-			source: TransactionSource,
-			tx: <Block as BlockT>::Extrinsic,
-			block_hash: <Block as BlockT>::Hash,
-		) -> TransactionValidity {
-			Executive::validate_transaction(source, tx, block_hash)
-		}
-	}
-
-	impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
-		fn offchain_worker(header: &<Block as BlockT>::Header) {
-			Executive::offchain_worker(header)
-		}
-	}
+	};
+}
 
+#[cfg(not(feature = "babe"))]
+impl_node_runtime_apis! {
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> sp_consensus_aura::SlotDuration {
 			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
@@ -462,128 +1693,50 @@ impl_runtime_apis! {
 			Aura::authorities().into_inner()
 		}
 	}
+}
 
-	impl sp_session::SessionKeys<Block> for Runtime {
-		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
-			opaque::SessionKeys::generate(seed)
-		}
-
-		fn decode_session_keys(
-			encoded: Vec<u8>,
-		) -> Option<Vec<(Vec<u8>, KeyTypeId)>> {
-			opaque::SessionKeys::decode_into_raw_public_keys(&encoded)
+#[cfg(feature = "babe")]
+impl_node_runtime_apis! {
+	impl sp_consensus_babe::BabeApi<Block> for Runtime {
+		fn configuration() -> sp_consensus_babe::BabeGenesisConfiguration {
+			sp_consensus_babe::BabeGenesisConfiguration {
+				slot_duration: Babe::slot_duration(),
+				epoch_length: EpochDuration::get(),
+				c: BABE_GENESIS_EPOCH_CONFIG.c,
+				genesis_authorities: Babe::authorities().to_vec(),
+				randomness: Babe::randomness(),
+				allowed_slots: BABE_GENESIS_EPOCH_CONFIG.allowed_slots,
+			}
 		}
-	}
 
-	impl fg_primitives::GrandpaApi<Block> for Runtime {
-		fn grandpa_authorities() -> GrandpaAuthorityList {
-			Grandpa::grandpa_authorities()
+		fn current_epoch_start() -> sp_consensus_babe::Slot {
+			Babe::current_epoch_start()
 		}
 
-		fn current_set_id() -> fg_primitives::SetId {
-			Grandpa::current_set_id()
+		fn current_epoch() -> sp_consensus_babe::Epoch {
+			Babe::current_epoch()
 		}
 
-		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
-				<Block as BlockT>::Hash,
-				NumberFor<Block>,
-			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
-		) -> Option<()> {
-			None
+		fn next_epoch() -> sp_consensus_babe::Epoch {
+			Babe::next_epoch()
 		}
 
 		fn generate_key_ownership_proof(
-			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
-		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
-		}
-	}
-
-	impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index> for Runtime {
-		fn account_nonce(account: AccountId) -> Index {
-			System::account_nonce(account)
+			_slot: sp_consensus_babe::Slot,
+			authority_id: sp_consensus_babe::AuthorityId,
+		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
+			Historical::prove((sp_consensus_babe::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(sp_consensus_babe::OpaqueKeyOwnershipProof::new)
 		}
-	}
 
-	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
-		fn query_info(
-			uxt: <Block as BlockT>::Extrinsic,
-			len: u32,
-		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-			TransactionPayment::query_info(uxt, len)
-		}
-		fn query_fee_details(
-			uxt: <Block as BlockT>::Extrinsic,
-			len: u32,
-		) -> pallet_transaction_payment::FeeDetails<Balance> {
-			TransactionPayment::query_fee_details(uxt, len)
-		}
-	}
-
-	#[cfg(feature = "runtime-benchmarks")]
-	impl frame_benchmarking::Benchmark<Block> for Runtime {
-		fn benchmark_metadata(extra: bool) -> (
-			Vec<frame_benchmarking::BenchmarkList>,
-			Vec<frame_support::traits::StorageInfo>,
-		) {
-			use frame_benchmarking::{list_benchmark, baseline, Benchmarking, BenchmarkList};
-			use frame_support::traits::StorageInfoTrait;
-			use frame_system_benchmarking::Pallet as SystemBench;
-			use baseline::Pallet as BaselineBench;
-
-			let mut list = Vec::<BenchmarkList>::new();
-
-			list_benchmark!(list, extra, frame_benchmarking, BaselineBench::<Runtime>);
-			list_benchmark!(list, extra, frame_system, SystemBench::<Runtime>);
-			list_benchmark!(list, extra, pallet_balances, Balances);
-			list_benchmark!(list, extra, pallet_timestamp, Timestamp);
-			list_benchmark!(list, extra, pallet_template, TemplateModule);
-
-			let storage_info = AllPalletsWithSystem::storage_info();
-
-			return (list, storage_info)
-		}
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: sp_consensus_babe::EquivocationProof<<Block as BlockT>::Header>,
+			key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
 
-		fn dispatch_benchmark(
-			config: frame_benchmarking::BenchmarkConfig
-		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
-			use frame_benchmarking::{baseline, Benchmarking, BenchmarkBatch, add_benchmark, TrackedStorageKey};
-
-			use frame_system_benchmarking::Pallet as SystemBench;
-			use baseline::Pallet as BaselineBench;
-
-			impl frame_system_benchmarking::Config for Runtime {}
-			impl baseline::Config for Runtime {}
-
-			let whitelist: Vec<TrackedStorageKey> = vec![
-				// Block Number
-				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
-				// Total Issuance
-				hex_literal::hex!("c2261276cc9d1f8598ea4b6a74b15c2f57c875e4cff74148e4628f264b974c80").to_vec().into(),
-				// Execution Phase
-				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a").to_vec().into(),
-				// Event Count
-				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850").to_vec().into(),
-				// System Events
-				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7").to_vec().into(),
-			];
-
-			let mut batches = Vec::<BenchmarkBatch>::new();
-			let params = (&config, &whitelist);
-
-			add_benchmark!(params, batches, frame_benchmarking, BaselineBench::<Runtime>);
-			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
-			add_benchmark!(params, batches, pallet_balances, Balances);
-			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
-			add_benchmark!(params, batches, pallet_template, TemplateModule);
-
-			Ok(batches)
+			Babe::submit_unsigned_equivocation_report(equivocation_proof, key_owner_proof)
 		}
 	}
 }
@@ -0,0 +1,60 @@
+//! Migrations run by `Executive` when the runtime is upgraded on a live chain.
+
+use crate::*;
+use frame_support::{storage::bounded_vec::BoundedVec, traits::OnRuntimeUpgrade};
+use sp_std::convert::TryFrom;
+
+/// Seeds `Session` and `ValidatorSet` on a chain started before they were added, so that the first
+/// session rotation keeps the current authorities instead of handing an empty set to Aura and
+/// Grandpa.
+///
+/// The Aura and Grandpa authorities are paired by position, and each validator account is the
+/// account of its Aura key, as in the chain specs the old chain was started from. Chains that
+/// already have session validators are left untouched.
+pub struct SeedSessionValidators;
+
+impl OnRuntimeUpgrade for SeedSessionValidators {
+	fn on_runtime_upgrade() -> Weight {
+		let db = <Runtime as frame_system::Config>::DbWeight::get();
+		if !Session::validators().is_empty() {
+			return db.reads(1)
+		}
+
+		let validators: Vec<(AccountId, opaque::SessionKeys)> = Aura::authorities()
+			.iter()
+			.cloned()
+			.zip(Grandpa::grandpa_authorities())
+			.map(|(aura, (grandpa, _))| {
+				let account = AccountId::from(sp_core::sr25519::Public::from(aura.clone()).0);
+				(account, opaque::SessionKeys { aura, grandpa })
+			})
+			.collect();
+		let accounts: Vec<AccountId> =
+			validators.iter().map(|(account, _)| account.clone()).collect();
+		let bounded = match BoundedVec::<_, MaxValidators>::try_from(accounts.clone()) {
+			Ok(bounded) if !accounts.is_empty() => bounded,
+			_ => return db.reads(3),
+		};
+
+		for (account, keys) in validators.iter() {
+			for id in opaque::SessionKeys::key_ids() {
+				pallet_session::KeyOwner::<Runtime>::insert(
+					(*id, keys.get_raw(*id).to_vec()),
+					account.clone(),
+				);
+			}
+			pallet_session::NextKeys::<Runtime>::insert(account, keys.clone());
+			// Session keys hold a consumer reference, which needs a provider, as at genesis.
+			if frame_system::Pallet::<Runtime>::inc_consumers(account).is_err() {
+				frame_system::Pallet::<Runtime>::inc_providers(account);
+				let _ = frame_system::Pallet::<Runtime>::inc_consumers(account);
+			}
+		}
+		let count = validators.len() as Weight;
+		pallet_session::Validators::<Runtime>::put(accounts);
+		pallet_session::QueuedKeys::<Runtime>::put(validators);
+		pallet_validator_set::Validators::<Runtime>::put(bounded);
+
+		db.reads_writes(3 + count, 3 + 4 * count)
+	}
+}
@@ -0,0 +1,334 @@
+//! Tests for the runtime's fee, sponsorship and proxy configuration.
+
+use crate::*;
+use frame_support::{
+	assert_ok,
+	traits::OnFinalize,
+	weights::{DispatchClass, DispatchInfo, GetDispatchInfo, Pays},
+};
+use sp_runtime::traits::{Dispatchable, One, SignedExtension};
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into()
+}
+
+/// The weight available to normal extrinsics in a block.
+fn max_normal_weight() -> Weight {
+	let weights = BlockWeights::get();
+	weights.get(DispatchClass::Normal).max_total.unwrap_or(weights.max_block)
+}
+
+/// Finalizes `blocks` blocks that each use `weight`, returning the fee multiplier after each.
+fn run_blocks(weight: Weight, blocks: u32) -> Vec<Multiplier> {
+	(1..=blocks)
+		.map(|n| {
+			System::set_block_consumed_resources(weight, 0);
+			TransactionPayment::on_finalize(n);
+			TransactionPayment::next_fee_multiplier()
+		})
+		.collect()
+}
+
+#[test]
+fn congested_blocks_raise_fees() {
+	new_test_ext().execute_with(|| {
+		let multipliers = run_blocks(max_normal_weight(), HOURS);
+
+		assert!(multipliers[0] > Multiplier::one());
+		assert!(multipliers.windows(2).all(|w| w[1] > w[0]));
+		// An hour of spam makes transactions more than twice as expensive.
+		assert!(multipliers[multipliers.len() - 1] > Multiplier::saturating_from_integer(2));
+	});
+}
+
+#[test]
+fn target_fullness_keeps_fees() {
+	new_test_ext().execute_with(|| {
+		let target = TargetBlockFullness::get() * max_normal_weight();
+
+		let multipliers = run_blocks(target, 10);
+
+		assert!(multipliers.iter().all(|m| *m == Multiplier::one()));
+	});
+}
+
+#[test]
+fn empty_blocks_lower_fees_to_minimum() {
+	new_test_ext().execute_with(|| {
+		let multipliers = run_blocks(0, DAYS);
+
+		assert!(multipliers[0] < Multiplier::one());
+		assert!(multipliers.windows(2).all(|w| w[1] <= w[0]));
+		assert_eq!(multipliers[multipliers.len() - 1], MinimumMultiplier::get());
+
+		// Fees recover from the minimum once blocks fill up again.
+		let multipliers = run_blocks(max_normal_weight(), 10);
+		assert!(multipliers[0] > MinimumMultiplier::get());
+	});
+}
+
+#[test]
+fn zero_weight_calls_pay_base_fee() {
+	new_test_ext().execute_with(|| {
+		let info = DispatchInfo { weight: 0, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+		let base_fee = ExtrinsicBaseFee::get();
+
+		// The polynomial coefficients round down, by less than one unit.
+		let fee = TransactionPayment::compute_fee(0, &info, 0);
+		assert!(fee <= base_fee && fee + 1 >= base_fee);
+
+		let fee_with_bytes = TransactionPayment::compute_fee(100, &info, 0);
+		assert_eq!(fee_with_bytes, fee + 100 * TransactionByteFee::get());
+	});
+}
+
+#[test]
+fn fees_scale_with_weight() {
+	new_test_ext().execute_with(|| {
+		let light = DispatchInfo { weight: 0, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+		let heavy = DispatchInfo { weight: 10 * ExtrinsicBaseWeight::get(), ..light };
+
+		let light_fee = TransactionPayment::compute_fee(0, &light, 0);
+		let heavy_fee = TransactionPayment::compute_fee(0, &heavy, 0);
+
+		assert!(heavy_fee >= 10 * light_fee);
+	});
+}
+
+#[test]
+fn fees_are_split_between_treasury_and_author() {
+	new_test_ext().execute_with(|| {
+		let fees = Balances::issue(10_000);
+		let tips = Balances::issue(1_000);
+
+		DealWithFees::on_unbalanceds(vec![fees, tips].into_iter());
+
+		assert_eq!(Balances::free_balance(&Treasury::account_id()), 8_000);
+		assert_eq!(Balances::free_balance(&Authorship::author()), 3_000);
+	});
+}
+
+/// Runs `call` from `who` through the sponsored fee extension and dispatches it.
+fn apply_sponsored(who: &AccountId, call: Call) {
+	let info = call.get_dispatch_info();
+	let len = call.encoded_size();
+	let pre = pallet_sponsorship::ChargeSponsoredTransactionPayment::<Runtime>::from(0)
+		.pre_dispatch(who, &call, &info, len)
+		.expect("the sponsor pays the fee");
+	let post_info = call.dispatch(Origin::signed(who.clone())).expect("the call succeeds");
+	assert_ok!(pallet_sponsorship::ChargeSponsoredTransactionPayment::<Runtime>::post_dispatch(
+		pre,
+		&info,
+		&post_info,
+		len,
+		&Ok(())
+	));
+}
+
+#[test]
+fn sponsored_player_without_tokens_creates_a_kitty() {
+	let sponsor = AccountId::from([1u8; 32]);
+	let player = AccountId::from([2u8; 32]);
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> { balances: vec![(sponsor.clone(), 1 << 60)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Sponsorship::set_budget(Origin::signed(sponsor.clone()), 1 << 40));
+		assert_ok!(Sponsorship::add_user(Origin::signed(sponsor.clone()), player.clone()));
+		assert_eq!(Balances::total_balance(&player), 0);
+
+		apply_sponsored(
+			&player,
+			Call::Sponsorship(pallet_sponsorship::Call::accept_sponsor {
+				sponsor: sponsor.clone(),
+			}),
+		);
+		assert_eq!(Sponsorship::sponsor_of(&player), Some(sponsor.clone()));
+		assert_eq!(Balances::free_balance(&player), SponsorshipEndowment::get());
+
+		apply_sponsored(&player, Call::KittiesModule(pallet_kitties::Call::create {}));
+		assert_eq!(KittiesModule::owner(1), Some(player.clone()));
+		// The stake is reserved from the endowment; every fee was paid by the sponsor.
+		assert_eq!(Balances::reserved_balance(&player), StakeAmountForKitty::get());
+		assert_eq!(Balances::free_balance(&player), ExistentialDeposit::get());
+	});
+}
+
+#[test]
+fn non_transfer_proxy_cannot_move_kitties_or_claims() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let owner = AccountId::from([1u8; 32]);
+		let other = AccountId::from([2u8; 32]);
+		let allowed = |call: Call| ProxyType::NonTransfer.filter(&call);
+
+		assert!(allowed(Call::KittiesModule(pallet_kitties::Call::create {})));
+		assert!(allowed(Call::KittiesModule(pallet_kitties::Call::sale {
+			kitty_id: 1,
+			price: None
+		})));
+		assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::sale {
+			kitty_id: 1,
+			price: Some(0)
+		})));
+		assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::transfer {
+			to: other.clone(),
+			kitty_id: 1
+		})));
+		assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::buy { kitty_id: 1 })));
+		assert!(!allowed(Call::Balances(pallet_balances::Call::transfer {
+			dest: other.clone().into(),
+			value: 1
+		})));
+
+		let proof = b"claim".to_vec();
+		assert!(allowed(Call::PoeModule(pallet_poe::Call::create_claim { proof: proof.clone() })));
+		assert!(!allowed(Call::PoeModule(pallet_poe::Call::transfer_claim {
+			claim: proof.clone(),
+			dest: other.clone()
+		})));
+		assert!(allowed(Call::PoeModule(pallet_poe::Call::propose_claim_action {
+			proof: proof.clone(),
+			action: pallet_poe::ClaimAction::Revoke(0)
+		})));
+		assert!(!allowed(Call::PoeModule(pallet_poe::Call::propose_claim_action {
+			proof: proof.clone(),
+			action: pallet_poe::ClaimAction::Transfer(other.clone())
+		})));
+
+		// Approving is only allowed when the pending action is not a transfer.
+		let approve =
+			|| Call::PoeModule(pallet_poe::Call::approve_claim_action { proof: proof.clone() });
+		assert_ok!(PoeModule::propose_multi_party_claim(
+			Origin::signed(owner.clone()),
+			proof.clone(),
+			vec![other.clone()],
+			2,
+			10
+		));
+		assert_ok!(PoeModule::approve_multi_party_claim(
+			Origin::signed(other.clone()),
+			proof.clone()
+		));
+		assert_ok!(PoeModule::propose_claim_action(
+			Origin::signed(owner.clone()),
+			proof.clone(),
+			pallet_poe::ClaimAction::Revoke(0)
+		));
+		assert!(allowed(approve()));
+
+		System::set_block_number(1 + ActionPeriod::get() + 1);
+		assert_ok!(PoeModule::propose_claim_action(
+			Origin::signed(owner),
+			proof.clone(),
+			pallet_poe::ClaimAction::Transfer(other)
+		));
+		assert!(!allowed(approve()));
+	});
+}
+
+#[test]
+fn non_transfer_proxy_cannot_move_assets() {
+	new_test_ext().execute_with(|| {
+		let other = AccountId::from([2u8; 32]);
+		let allowed = |call: Call| ProxyType::NonTransfer.filter(&call);
+
+		assert!(allowed(Call::AssetFees(pallet_asset_fees::Call::set_fee_asset {
+			asset_id: Some(1)
+		})));
+		assert!(allowed(Call::KittiesModule(pallet_kitties::Call::sale_in_asset {
+			kitty_id: 1,
+			asset_id: 1,
+			price: None
+		})));
+		assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::sale_in_asset {
+			kitty_id: 1,
+			asset_id: 1,
+			price: Some(1)
+		})));
+		assert!(!allowed(Call::Assets(pallet_assets::Call::transfer {
+			id: 1,
+			target: other.clone().into(),
+			amount: 1
+		})));
+		assert!(!allowed(Call::Assets(pallet_assets::Call::transfer_keep_alive {
+			id: 1,
+			target: other.clone().into(),
+			amount: 1
+		})));
+		assert!(!allowed(Call::Assets(pallet_assets::Call::approve_transfer {
+			id: 1,
+			delegate: other.clone().into(),
+			amount: 1
+		})));
+		assert!(!allowed(Call::Assets(pallet_assets::Call::transfer_approved {
+			id: 1,
+			owner: other.clone().into(),
+			destination: other.into(),
+			amount: 1
+		})));
+	});
+}
+
+#[test]
+fn non_transfer_proxy_cannot_call_contracts() {
+	new_test_ext().execute_with(|| {
+		let contract = AccountId::from([3u8; 32]);
+		let call = Call::Contracts(pallet_contracts::Call::call {
+			dest: contract.into(),
+			value: 0,
+			gas_limit: 1_000_000,
+			data: vec![],
+		});
+		assert!(!ProxyType::NonTransfer.filter(&call));
+		assert!(ProxyType::Any.filter(&call));
+	});
+}
+
+#[cfg(all(not(feature = "babe"), not(feature = "npos")))]
+#[test]
+fn upgrade_seeds_session_and_validator_set_from_the_authorities() {
+	use frame_support::traits::{GenesisBuild, OnRuntimeUpgrade};
+	use sp_core::{crypto::key_types, ed25519, sr25519};
+
+	let authorities: Vec<(AuraId, GrandpaId)> = (1u8..=2)
+		.map(|i| {
+			(sr25519::Public::from_raw([i; 32]).into(), ed25519::Public::from_raw([i; 32]).into())
+		})
+		.collect();
+	// The state of a chain started before `Session` and `ValidatorSet` were added.
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	let aura = pallet_aura::GenesisConfig::<Runtime> {
+		authorities: authorities.iter().map(|(aura, _)| aura.clone()).collect(),
+	};
+	GenesisBuild::<Runtime>::assimilate_storage(&aura, &mut storage).unwrap();
+	let grandpa = pallet_grandpa::GenesisConfig {
+		authorities: authorities.iter().map(|(_, grandpa)| (grandpa.clone(), 1)).collect(),
+	};
+	GenesisBuild::<Runtime>::assimilate_storage(&grandpa, &mut storage).unwrap();
+
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		migrations::SeedSessionValidators::on_runtime_upgrade();
+
+		let accounts: Vec<AccountId> = (1u8..=2).map(|i| AccountId::from([i; 32])).collect();
+		assert_eq!(Session::validators(), accounts);
+		assert_eq!(ValidatorSet::validators().into_inner(), accounts);
+		let (aura, grandpa) = authorities[1].clone();
+		assert_eq!(
+			pallet_session::NextKeys::<Runtime>::get(&accounts[1]),
+			Some(opaque::SessionKeys { aura, grandpa: grandpa.clone() })
+		);
+		assert_eq!(
+			pallet_session::KeyOwner::<Runtime>::get((key_types::GRANDPA, grandpa.encode())),
+			Some(accounts[1].clone())
+		);
+		assert_eq!(System::consumers(&accounts[1]), 1);
+
+		// A second upgrade leaves the seeded state alone.
+		migrations::SeedSessionValidators::on_runtime_upgrade();
+		assert_eq!(System::consumers(&accounts[1]), 1);
+	});
+}